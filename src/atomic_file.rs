@@ -0,0 +1,53 @@
+//! Replacing files atomically, so readers and interrupted writers never leave
+//! a partially written file in place, and locking them between processes
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// Write `content` to a temporary file next to `path`, then rename it over
+/// `path` so readers never see a partially written file
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let tmp_path = tmp_path(path);
+    let result = (|| -> Result<()> {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// `<path>.tmp.<pid>`: next to `path`, so renaming it over `path` never crosses
+/// file systems, and unique per process
+pub(crate) fn tmp_path(path: &Path) -> OsString {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".tmp.{}", std::process::id()));
+    tmp_path
+}
+
+/// Take an exclusive advisory lock on `<path>.lock`, released when the
+/// returned file is dropped
+///
+/// Blocks while another process holds the lock.
+pub fn lock_file(path: &Path) -> Result<File> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_path = Path::new(&lock_path);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)
+        .with_context(|| format!("Failed to open lock file {}", lock_path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", path.display()))?;
+    Ok(file)
+}
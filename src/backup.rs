@@ -0,0 +1,351 @@
+//! Library backups in JSON Lines, gzipped when the file name ends in `.gz`
+//!
+//! A backup starts with a [`BackupRecord::Header`], followed by one record per
+//! tag and per document. [`BackupWriter`] writes to a temporary file and only
+//! renames it into place in [`BackupWriter::finish`], so an interrupted backup
+//! never replaces a good one. [`BackupReader`] detects compression from the
+//! file's contents rather than its name.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::atomic_file::tmp_path;
+use crate::cache::now_secs;
+use crate::types::{Document, Tag};
+
+/// Format version written to new backups; newer backups are refused
+pub const BACKUP_VERSION: u32 = 1;
+
+/// One line of a backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackupRecord {
+    Header {
+        version: u32,
+        /// Unix time (seconds) the backup was started
+        created_at: u64,
+        /// Whether documents include `html_content`
+        with_html_content: bool,
+    },
+    Tag(Tag),
+    Document(Box<Document>),
+}
+
+enum Sink {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+/// Streams records into a new backup file
+///
+/// Dropping the writer without a successful [`BackupWriter::finish`] removes
+/// the temporary file, e.g. when a request fails halfway through a backup.
+pub struct BackupWriter {
+    /// Taken by [`BackupWriter::finish`]
+    sink: Option<Sink>,
+    path: PathBuf,
+    tmp_path: OsString,
+    /// Set once the backup has been renamed into place
+    finished: bool,
+}
+
+impl BackupWriter {
+    /// Start a backup at `path`, writing its header
+    pub fn create(path: impl Into<PathBuf>, with_html_content: bool) -> Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+        }
+        let tmp_path = tmp_path(&path);
+        let file = BufWriter::new(
+            File::create(&tmp_path)
+                .with_context(|| format!("Failed to create backup {}", path.display()))?,
+        );
+        let sink = if path.extension().is_some_and(|ext| ext == "gz") {
+            Sink::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            Sink::Plain(file)
+        };
+
+        let mut writer = Self {
+            sink: Some(sink),
+            path,
+            tmp_path,
+            finished: false,
+        };
+        writer.write(&BackupRecord::Header {
+            version: BACKUP_VERSION,
+            created_at: now_secs(),
+            with_html_content,
+        })?;
+        Ok(writer)
+    }
+
+    /// Path the backup is renamed to when finished
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&mut self, record: &BackupRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let result = match self.sink.as_mut().expect("backup already finished") {
+            Sink::Plain(w) => w.write_all(&line),
+            Sink::Gzip(w) => w.write_all(&line),
+        };
+        result.with_context(|| format!("Failed to write backup {}", self.path.display()))
+    }
+
+    /// Flush everything to disk and move the backup into place
+    pub fn finish(mut self) -> Result<()> {
+        let sink = self.sink.take().expect("backup already finished");
+        (|| -> Result<()> {
+            let file = match sink {
+                Sink::Plain(w) => w.into_inner()?,
+                Sink::Gzip(w) => w.finish()?.into_inner()?,
+            };
+            file.sync_all()?;
+            fs::rename(&self.tmp_path, &self.path)?;
+            Ok(())
+        })()
+        .with_context(|| format!("Failed to write backup {}", self.path.display()))?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for BackupWriter {
+    fn drop(&mut self) {
+        if !self.finished {
+            // Close the file before removing it
+            drop(self.sink.take());
+            let _ = fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+/// Iterates over the tag and document records of a backup
+pub struct BackupReader {
+    lines: std::io::Lines<Box<dyn BufRead>>,
+    line_no: usize,
+    path: PathBuf,
+    /// Whether documents include `html_content`
+    pub with_html_content: bool,
+    /// Unix time (seconds) the backup was started
+    pub created_at: u64,
+}
+
+impl BackupReader {
+    /// Open the backup at `path` and check its header
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut file = BufReader::new(
+            File::open(&path)
+                .with_context(|| format!("Failed to open backup {}", path.display()))?,
+        );
+        let gzipped = file.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        let input: Box<dyn BufRead> = if gzipped {
+            Box::new(BufReader::new(MultiGzDecoder::new(file)))
+        } else {
+            Box::new(file)
+        };
+
+        let mut reader = Self {
+            lines: input.lines(),
+            line_no: 0,
+            path,
+            with_html_content: false,
+            created_at: 0,
+        };
+        match reader.next() {
+            Some(Ok(BackupRecord::Header {
+                version,
+                created_at,
+                with_html_content,
+            })) => {
+                if version > BACKUP_VERSION {
+                    bail!(
+                        "Backup {} has format version {}, but this rr reads up to version {}",
+                        reader.path.display(),
+                        version,
+                        BACKUP_VERSION
+                    );
+                }
+                reader.created_at = created_at;
+                reader.with_html_content = with_html_content;
+                Ok(reader)
+            }
+            Some(Err(e)) => {
+                Err(e.context(format!("{} is not an rr backup", reader.path.display())))
+            }
+            _ => bail!("{} is not an rr backup", reader.path.display()),
+        }
+    }
+}
+
+impl Iterator for BackupReader {
+    type Item = Result<BackupRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line_no += 1;
+            let line =
+                match self.lines.next()? {
+                    Ok(line) => line,
+                    Err(e) => {
+                        return Some(Err(e).with_context(|| {
+                            format!("Failed to read backup {}", self.path.display())
+                        }))
+                    }
+                };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_str(&line).with_context(|| {
+                format!(
+                    "Invalid record on line {} of backup {}",
+                    self.line_no,
+                    self.path.display()
+                )
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(id: &str) -> Document {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "source_url": format!("https://example.com/{}", id),
+            "title": "Title",
+            "html_content": "<p>hi</p>",
+        }))
+        .unwrap()
+    }
+
+    fn round_trip(file_name: &str) -> (PathBuf, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join(file_name);
+        let mut writer = BackupWriter::create(&path, true).unwrap();
+        writer
+            .write(&BackupRecord::Tag(Tag {
+                key: "rust".to_string(),
+                name: "Rust".to_string(),
+            }))
+            .unwrap();
+        writer
+            .write(&BackupRecord::Document(Box::new(document("d1"))))
+            .unwrap();
+        assert!(!path.exists(), "backup renamed into place before finish");
+        writer.finish().unwrap();
+
+        let reader = BackupReader::open(&path).unwrap();
+        assert!(reader.with_html_content);
+        assert!(reader.created_at > 0);
+        let records: Vec<BackupRecord> = reader.map(Result::unwrap).collect();
+        assert!(
+            matches!(&records[..], [BackupRecord::Tag(tag), BackupRecord::Document(doc)]
+            if tag.key == "rust" && doc.id == "d1" && doc.html_content.as_deref() == Some("<p>hi</p>"))
+        );
+        (path, dir)
+    }
+
+    #[test]
+    fn plain_backup_round_trips() {
+        let (path, _dir) = round_trip("backup.jsonl");
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(r#"{"type":"header","version":1,"#));
+        assert_eq!(content.lines().count(), 3);
+    }
+
+    #[test]
+    fn gzipped_backup_round_trips() {
+        let (path, _dir) = round_trip("backup.jsonl.gz");
+        assert!(fs::read(&path).unwrap().starts_with(&[0x1f, 0x8b]));
+
+        // Compression is detected from the contents, not the name
+        let renamed = path.with_file_name("backup.jsonl");
+        fs::rename(&path, &renamed).unwrap();
+        assert_eq!(BackupReader::open(&renamed).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn newer_version_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.jsonl");
+        let header = BackupRecord::Header {
+            version: BACKUP_VERSION + 1,
+            created_at: 1,
+            with_html_content: false,
+        };
+        fs::write(&path, serde_json::to_string(&header).unwrap()).unwrap();
+
+        let error = BackupReader::open(&path).err().unwrap();
+        assert!(error.to_string().contains("format version 2"), "{}", error);
+    }
+
+    #[test]
+    fn file_without_a_header_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.jsonl");
+        fs::write(&path, r#"{"type":"tag","key":"a","name":"a"}"#).unwrap();
+        assert!(BackupReader::open(&path).is_err());
+        fs::write(&path, "").unwrap();
+        assert!(BackupReader::open(&path).is_err());
+    }
+
+    #[test]
+    fn invalid_record_reports_its_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.jsonl");
+        let header = serde_json::to_string(&BackupRecord::Header {
+            version: BACKUP_VERSION,
+            created_at: 1,
+            with_html_content: false,
+        })
+        .unwrap();
+        fs::write(&path, format!("{}\n\n{{oops\n", header)).unwrap();
+
+        let error = BackupReader::open(&path)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert!(error.to_string().contains("line 3"), "{}", error);
+    }
+
+    #[test]
+    fn unfinished_backup_leaves_no_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.jsonl.gz");
+        let mut writer = BackupWriter::create(&path, false).unwrap();
+        writer
+            .write(&BackupRecord::Document(Box::new(document("d1"))))
+            .unwrap();
+        drop(writer);
+
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn unfinished_backup_keeps_the_previous_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.jsonl");
+        fs::write(&path, "previous").unwrap();
+
+        drop(BackupWriter::create(&path, false).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "previous");
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::{
+    ensure_parent_dir, AccountDocuments, CacheEntry, CacheFile, MergeCounts, Removal, SyncState,
+};
+use crate::atomic_file::{lock_file, write_atomic};
+use crate::types::Document;
+
+/// JSON file store
+///
+/// Several processes may share one cache file: [`JsonStore::save`] takes an
+/// advisory lock, merges this process's changes into whatever is on disk and
+/// replaces the file atomically.
+pub(crate) struct JsonStore {
+    file_path: String,
+    data: CacheFile,
+    /// Keys set since the cache was opened
+    written: HashSet<String>,
+    /// Keys removed since the cache was opened
+    removed: HashSet<String>,
+    /// Bulk removals since the cache was opened, replayed on entries other
+    /// processes saved in the meantime
+    removals: Vec<Removal>,
+    /// Accounts and ids of documents stored since the cache was opened
+    written_documents: HashSet<(String, String)>,
+    /// Accounts whose sync state was recorded since the cache was opened
+    written_sync: HashSet<String>,
+}
+
+impl JsonStore {
+    pub(crate) fn open(file_path: &str) -> Self {
+        let data = load_from_file(file_path).unwrap_or_default();
+        Self {
+            file_path: file_path.to_string(),
+            data,
+            written: HashSet::new(),
+            removed: HashSet::new(),
+            removals: vec![],
+            written_documents: HashSet::new(),
+            written_sync: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn file_path(&self) -> &str {
+        &self.file_path
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&CacheEntry> {
+        self.data.entries.get(key)
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&String, &CacheEntry)> {
+        self.data.entries.iter()
+    }
+
+    pub(crate) fn set(&mut self, key: &str, entry: CacheEntry) {
+        self.removed.remove(key);
+        self.written.insert(key.to_string());
+        self.data.entries.insert(key.to_string(), entry);
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<CacheEntry> {
+        self.written.remove(key);
+        self.removed.insert(key.to_string());
+        self.data.entries.remove(key)
+    }
+
+    pub(crate) fn apply(&mut self, removal: Removal) -> usize {
+        let before = self.data.entries.len();
+        self.data.entries.retain(|_, entry| !removal.matches(entry));
+        self.removals.push(removal);
+        before - self.data.entries.len()
+    }
+
+    pub(crate) fn document(&self, account: &str, id: &str) -> Option<&Document> {
+        let stored = self.data.accounts.get(account)?;
+        stored
+            .documents
+            .get(id)
+            .filter(|_| !stored.deleted.contains_key(id))
+    }
+
+    pub(crate) fn documents(&self, account: &str) -> impl Iterator<Item = &Document> {
+        self.data
+            .accounts
+            .get(account)
+            .into_iter()
+            .flat_map(|stored| {
+                stored
+                    .documents
+                    .values()
+                    .filter(|doc| !stored.deleted.contains_key(&doc.id))
+            })
+    }
+
+    pub(crate) fn total_documents(&self) -> usize {
+        self.data
+            .accounts
+            .keys()
+            .map(|account| self.documents(account).count())
+            .sum()
+    }
+
+    pub(crate) fn merge_documents(&mut self, account: &str, documents: &[Document]) -> MergeCounts {
+        let mut counts = MergeCounts::default();
+        for doc in documents {
+            match self.document(account, &doc.id) {
+                None => counts.new += 1,
+                Some(stored) if stored.updated_at != doc.updated_at => counts.changed += 1,
+                Some(_) => counts.unchanged += 1,
+            }
+            self.written_documents
+                .insert((account.to_string(), doc.id.clone()));
+            let stored = self.data.accounts.entry(account.to_string()).or_default();
+            stored.deleted.remove(&doc.id);
+            stored.documents.insert(doc.id.clone(), doc.clone());
+        }
+        counts
+    }
+
+    pub(crate) fn tombstone_documents(
+        &mut self,
+        account: &str,
+        ids: &[String],
+        deleted_at: u64,
+    ) -> usize {
+        let mut count = 0;
+        for id in ids {
+            if self.document(account, id).is_some() {
+                self.written_documents
+                    .insert((account.to_string(), id.clone()));
+                let stored = self.data.accounts.entry(account.to_string()).or_default();
+                stored.deleted.insert(id.clone(), deleted_at);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    pub(crate) fn sync_state(&self, account: &str) -> Option<&SyncState> {
+        self.data.sync.get(account)
+    }
+
+    pub(crate) fn set_sync_state(&mut self, account: &str, state: SyncState) {
+        self.written_sync.insert(account.to_string());
+        self.data.sync.insert(account.to_string(), state);
+    }
+
+    /// Merge this process's changes into the cache file
+    ///
+    /// Entries saved by other processes since this cache was opened are kept
+    /// unless this process removed them; for keys both wrote, the newer entry
+    /// wins. The same goes for stored documents, compared by `updated_at`.
+    pub(crate) fn save(&self) -> Result<()> {
+        ensure_parent_dir(&self.file_path)?;
+        let _lock = lock_file(Path::new(&self.file_path))?;
+
+        let mut merged = load_from_file(&self.file_path).unwrap_or_default();
+        for removal in &self.removals {
+            merged.entries.retain(|_, entry| !removal.matches(entry));
+        }
+        for key in &self.removed {
+            merged.entries.remove(key);
+        }
+        for key in &self.written {
+            let Some(entry) = self.data.entries.get(key) else {
+                continue;
+            };
+            let newer_on_disk = merged
+                .entries
+                .get(key)
+                .is_some_and(|existing| existing.timestamp > entry.timestamp);
+            if !newer_on_disk {
+                merged.entries.insert(key.clone(), entry.clone());
+            }
+        }
+
+        for (account, id) in &self.written_documents {
+            let Some(stored) = self.data.accounts.get(account) else {
+                continue;
+            };
+            let Some(doc) = stored.documents.get(id) else {
+                continue;
+            };
+            let on_disk = merged.accounts.entry(account.clone()).or_default();
+            let newer_on_disk = on_disk
+                .documents
+                .get(id)
+                .is_some_and(|existing| existing.updated_at > doc.updated_at);
+            if !newer_on_disk {
+                on_disk.documents.insert(id.clone(), doc.clone());
+                match stored.deleted.get(id) {
+                    Some(deleted_at) => on_disk.deleted.insert(id.clone(), *deleted_at),
+                    None => on_disk.deleted.remove(id),
+                };
+            }
+        }
+        for account in &self.written_sync {
+            if let Some(state) = self.data.sync.get(account) {
+                merged.sync.insert(account.clone(), state.clone());
+            }
+        }
+
+        let content = serde_json::to_string_pretty(&merged)?;
+        write_atomic(Path::new(&self.file_path), content.as_bytes())
+    }
+}
+
+/// Read the cache file; an unparseable file is moved aside with a warning
+/// rather than silently replaced
+pub(crate) fn load_from_file(file_path: &str) -> Option<CacheFile> {
+    let path = Path::new(file_path);
+    if !path.exists() {
+        return None;
+    }
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Warning: Failed to read cache file {}: {}", file_path, e);
+            return None;
+        }
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(mut data) => {
+            upgrade_legacy_documents(&mut data);
+            Some(data)
+        }
+        Err(e) => {
+            let backup = format!("{}.corrupt", file_path);
+            eprintln!(
+                "Warning: Cache file {} is corrupt ({}); moved it to {} and starting empty",
+                file_path, e, backup
+            );
+            let _ = fs::rename(path, &backup);
+            None
+        }
+    }
+}
+
+/// Move documents stored before the store was kept per account into
+/// `accounts`
+///
+/// They can only have come from the one account that synced; if several
+/// did, they are dropped along with the sync progress, so the next `rr sync`
+/// of each account fetches its documents again.
+fn upgrade_legacy_documents(data: &mut CacheFile) {
+    if data.legacy_documents.is_empty() && data.legacy_deleted.is_empty() {
+        return;
+    }
+    let documents = std::mem::take(&mut data.legacy_documents);
+    let deleted = std::mem::take(&mut data.legacy_deleted);
+    match data.sync.keys().collect::<Vec<_>>()[..] {
+        [account] => {
+            let account = account.clone();
+            data.accounts
+                .insert(account, AccountDocuments { documents, deleted });
+        }
+        _ => data.sync.clear(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::now_secs;
+
+    fn entry(endpoint: &str, timestamp: u64) -> CacheEntry {
+        CacheEntry {
+            timestamp,
+            endpoint: endpoint.to_string(),
+            params: serde_json::json!({}),
+            response: serde_json::json!(timestamp),
+        }
+    }
+
+    fn document(id: &str, updated_at: &str) -> Document {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "source_url": null,
+            "title": null,
+            "updated_at": updated_at,
+        }))
+        .unwrap()
+    }
+
+    fn cache_path(dir: &tempfile::TempDir) -> String {
+        dir.path().join("cache.json").to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn save_keeps_keys_written_by_another_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = cache_path(&dir);
+        let mut first = JsonStore::open(&path);
+        let mut second = JsonStore::open(&path);
+
+        second.set("b", entry("list", 1));
+        second.save().unwrap();
+        first.set("a", entry("list", 1));
+        first.save().unwrap();
+
+        let file = load_from_file(&path).unwrap();
+        assert!(file.entries.contains_key("a"));
+        assert!(file.entries.contains_key("b"));
+    }
+
+    #[test]
+    fn save_replays_removals_on_entries_saved_meanwhile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = cache_path(&dir);
+        let mut first = JsonStore::open(&path);
+        let mut second = JsonStore::open(&path);
+
+        second.set("list:1", entry("list", now_secs()));
+        second.set("tags", entry("tag_list", now_secs()));
+        second.set("gone", entry("tag_list", now_secs()));
+        second.save().unwrap();
+        first.apply(Removal {
+            endpoint: Some("list".to_string()),
+            before: now_secs() + 1,
+        });
+        first.remove("gone");
+        first.save().unwrap();
+
+        let file = load_from_file(&path).unwrap();
+        assert!(!file.entries.contains_key("list:1"));
+        assert!(!file.entries.contains_key("gone"));
+        assert!(file.entries.contains_key("tags"));
+    }
+
+    #[test]
+    fn save_keeps_the_newer_entry_for_a_key_both_wrote() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = cache_path(&dir);
+        let mut first = JsonStore::open(&path);
+        let mut second = JsonStore::open(&path);
+
+        first.set("older_here", entry("list", 100));
+        first.set("newer_here", entry("list", 300));
+        second.set("older_here", entry("list", 200));
+        second.set("newer_here", entry("list", 200));
+        second.save().unwrap();
+        first.save().unwrap();
+
+        let file = load_from_file(&path).unwrap();
+        assert_eq!(file.entries["older_here"].timestamp, 200);
+        assert_eq!(file.entries["newer_here"].timestamp, 300);
+    }
+
+    #[test]
+    fn save_keeps_the_newer_document_and_tombstones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = cache_path(&dir);
+        let mut first = JsonStore::open(&path);
+        let mut second = JsonStore::open(&path);
+
+        first.merge_documents(
+            "a",
+            &[document("d1", "2024-01-01"), document("d2", "2024-01-01")],
+        );
+        first.tombstone_documents("a", &["d2".to_string()], 42);
+        second.merge_documents("a", &[document("d1", "2024-02-01")]);
+        second.merge_documents("b", &[document("d1", "2023-01-01")]);
+        second.save().unwrap();
+        first.save().unwrap();
+
+        let file = load_from_file(&path).unwrap();
+        let stored = &file.accounts["a"];
+        assert_eq!(
+            stored.documents["d1"].updated_at.as_deref(),
+            Some("2024-02-01")
+        );
+        assert_eq!(stored.deleted.get("d2"), Some(&42));
+        assert_eq!(
+            file.accounts["b"].documents["d1"].updated_at.as_deref(),
+            Some("2023-01-01")
+        );
+    }
+
+    #[test]
+    fn documents_from_before_accounts_go_to_the_one_synced_account() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = cache_path(&dir);
+        let legacy = serde_json::json!({
+            "entries": {},
+            "documents": {"d1": document("d1", "2024-01-01"), "d2": document("d2", "2024-01-01")},
+            "deleted": {"d2": 42},
+            "sync": {"a": {"updated_at": "2024-01-01", "synced_at": 1, "reconciled_at": 1}},
+        });
+        fs::write(&path, legacy.to_string()).unwrap();
+
+        let store = JsonStore::open(&path);
+        let ids: Vec<_> = store.documents("a").map(|doc| doc.id.as_str()).collect();
+        assert_eq!(ids, ["d1"]);
+        assert!(store.sync_state("a").is_some());
+
+        store.save().unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(saved.get("documents").is_none());
+        assert_eq!(saved["accounts"]["a"]["deleted"]["d2"], 42);
+    }
+
+    #[test]
+    fn documents_from_before_accounts_are_dropped_if_several_synced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = cache_path(&dir);
+        let legacy = serde_json::json!({
+            "entries": {},
+            "documents": {"d1": document("d1", "2024-01-01")},
+            "sync": {"a": {"updated_at": "2024-01-01"}, "b": {"updated_at": "2024-01-01"}},
+        });
+        fs::write(&path, legacy.to_string()).unwrap();
+
+        let store = JsonStore::open(&path);
+        assert_eq!(store.total_documents(), 0);
+        assert!(store.sync_state("a").is_none());
+        assert!(store.sync_state("b").is_none());
+    }
+
+    #[test]
+    fn corrupt_file_is_moved_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = cache_path(&dir);
+        fs::write(&path, "{not json").unwrap();
+
+        let mut store = JsonStore::open(&path);
+        assert_eq!(store.entries().count(), 0);
+        assert_eq!(
+            fs::read_to_string(format!("{}.corrupt", path)).unwrap(),
+            "{not json"
+        );
+
+        store.set("a", entry("list", 1));
+        store.save().unwrap();
+        assert!(load_from_file(&path).unwrap().entries.contains_key("a"));
+    }
+}
@@ -0,0 +1,789 @@
+mod json;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+use crate::types::{parse_timestamp, Document, ListDocumentsParams};
+use json::JsonStore;
+#[cfg(feature = "sqlite")]
+use sqlite::SqliteStore;
+
+/// A cached API response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Unix time (seconds) the entry was written
+    pub timestamp: u64,
+    /// Endpoint name, e.g. `list` or `tag_list`
+    pub endpoint: String,
+    /// Request parameters, kept for inspection
+    pub params: serde_json::Value,
+    /// Raw response body
+    pub response: serde_json::Value,
+}
+
+/// Summary of a cache's contents, see [`Cache::stats`]
+#[derive(Debug, Serialize, Default)]
+pub struct CacheStats {
+    pub entries: usize,
+    /// Serialized size of all entries in bytes
+    pub size_bytes: usize,
+    pub endpoints: BTreeMap<String, EndpointStats>,
+    /// Timestamp of the oldest entry
+    pub oldest: Option<u64>,
+    /// Timestamp of the newest entry
+    pub newest: Option<u64>,
+    /// Documents in the local document store, across accounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documents: Option<usize>,
+}
+
+/// Per-endpoint part of [`CacheStats`]
+#[derive(Debug, Serialize, Default)]
+pub struct EndpointStats {
+    pub entries: usize,
+    pub size_bytes: usize,
+}
+
+/// On-disk layout of the cache file
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CacheFile {
+    pub entries: HashMap<String, CacheEntry>,
+    /// Local document stores, keyed by account id
+    #[serde(default)]
+    pub accounts: HashMap<String, AccountDocuments>,
+    /// Sync progress, keyed by account id
+    #[serde(default)]
+    pub sync: HashMap<String, SyncState>,
+    /// Documents stored before they were kept per account, moved into
+    /// `accounts` on load
+    #[serde(default, rename = "documents", skip_serializing)]
+    pub(crate) legacy_documents: HashMap<String, Document>,
+    #[serde(default, rename = "deleted", skip_serializing)]
+    pub(crate) legacy_deleted: HashMap<String, u64>,
+}
+
+/// The documents one account synced
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct AccountDocuments {
+    /// Stored documents, keyed by document id
+    #[serde(default)]
+    pub documents: HashMap<String, Document>,
+    /// Tombstones: ids of stored documents found deleted in Reader, with the
+    /// Unix time (seconds) they were found missing
+    #[serde(default)]
+    pub deleted: HashMap<String, u64>,
+}
+
+/// Progress of `rr sync` for one account
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SyncState {
+    /// Highest `updated_at` seen; the next sync only asks for documents
+    /// updated after it
+    pub updated_at: Option<String>,
+    /// Unix time (seconds) the last sync completed
+    pub synced_at: Option<u64>,
+    /// Unix time (seconds) local documents were last checked against the
+    /// full list of ids on the server
+    pub reconciled_at: Option<u64>,
+}
+
+/// How [`Cache::merge_documents`] changed the document store
+#[derive(Debug, Clone, Copy, Serialize, Default)]
+pub struct MergeCounts {
+    /// Documents not stored before (or stored as deleted)
+    pub new: usize,
+    /// Stored documents with a different `updated_at`
+    pub changed: usize,
+    /// Stored documents with the same `updated_at`
+    pub unchanged: usize,
+}
+
+impl std::ops::AddAssign for MergeCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.new += other.new;
+        self.changed += other.changed;
+        self.unchanged += other.unchanged;
+    }
+}
+
+/// What [`Cache::import_json`] copied
+#[derive(Debug, Clone, Copy, Serialize, Default)]
+pub struct ImportCounts {
+    pub entries: usize,
+    /// Stored documents, not counting tombstoned ones
+    pub documents: usize,
+}
+
+/// Cache of API responses, keyed by caller-chosen strings
+///
+/// Backed either by a JSON file ([`Cache::new`]) or, with the `sqlite`
+/// feature, by an SQLite database ([`Cache::open_sqlite`]) that keeps the
+/// document store as an indexed table.
+///
+/// Entries expire after their endpoint's TTL (see [`Cache::set_ttl`]);
+/// endpoints without a TTL never expire. Besides responses, both backends
+/// hold a local document store per account, keyed by document id (see
+/// [`Cache::merge_documents`]), which never expires. `account` should be
+/// [`ReaderClient::account_id`](crate::ReaderClient::account_id), as for the
+/// cache keys.
+pub struct Cache {
+    store: Store,
+    ttls: HashMap<String, Duration>,
+    refresh: bool,
+}
+
+enum Store {
+    Json(Box<JsonStore>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteStore),
+}
+
+/// Drops entries of `endpoint` (or of every endpoint) written before `before`
+#[derive(Debug)]
+pub(crate) struct Removal {
+    endpoint: Option<String>,
+    before: u64,
+}
+
+impl Removal {
+    fn matches(&self, entry: &CacheEntry) -> bool {
+        entry.timestamp < self.before
+            && self
+                .endpoint
+                .as_ref()
+                .is_none_or(|endpoint| *endpoint == entry.endpoint)
+    }
+}
+
+impl Cache {
+    /// Open the JSON cache at `file_path`, starting empty if it is missing or unreadable
+    pub fn new(file_path: &str) -> Self {
+        Self::with_store(Store::Json(Box::new(JsonStore::open(file_path))))
+    }
+
+    /// Open (or create) the SQLite cache at `file_path`
+    #[cfg(feature = "sqlite")]
+    pub fn open_sqlite(file_path: &str) -> Result<Self> {
+        Ok(Self::with_store(Store::Sqlite(SqliteStore::open(
+            file_path,
+        )?)))
+    }
+
+    fn with_store(store: Store) -> Self {
+        Self {
+            store,
+            ttls: HashMap::new(),
+            refresh: false,
+        }
+    }
+
+    /// Expire entries of `endpoint` once they are older than `ttl`
+    pub fn set_ttl(&mut self, endpoint: &str, ttl: Duration) {
+        self.ttls.insert(endpoint.to_string(), ttl);
+    }
+
+    /// In refresh mode [`Cache::get`] always misses, but [`Cache::set`] still
+    /// stores fresh responses
+    pub fn set_refresh(&mut self, refresh: bool) {
+        self.refresh = refresh;
+    }
+
+    /// Persist changes
+    ///
+    /// The JSON store merges this process's changes into the file under a
+    /// lock, keeping entries other processes saved in the meantime; the
+    /// SQLite store writes immediately, so this is a no-op.
+    pub fn save(&self) -> Result<()> {
+        match &self.store {
+            Store::Json(store) => store.save(),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(_) => Ok(()),
+        }
+    }
+
+    /// Look up an entry by key, ignoring it if it has expired
+    pub fn get(&self, key: &str) -> Option<CacheEntry> {
+        if self.refresh {
+            return None;
+        }
+        self.entry(key).filter(|entry| !self.is_expired(entry))
+    }
+
+    /// Look up an entry by key, even if it has expired
+    pub fn entry(&self, key: &str) -> Option<CacheEntry> {
+        match &self.store {
+            Store::Json(store) => store.get(key).cloned(),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.get(key).unwrap_or_else(|e| {
+                eprintln!("Warning: Failed to read cache entry {}: {}", key, e);
+                None
+            }),
+        }
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        match self.ttls.get(&entry.endpoint) {
+            Some(ttl) => now_secs().saturating_sub(entry.timestamp) >= ttl.as_secs(),
+            None => false,
+        }
+    }
+
+    /// Path of the backing cache file
+    pub fn file_path(&self) -> &str {
+        match &self.store {
+            Store::Json(store) => store.file_path(),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.file_path(),
+        }
+    }
+
+    /// All entries, including expired ones
+    pub fn entries(&self) -> Result<Vec<(String, CacheEntry)>> {
+        match &self.store {
+            Store::Json(store) => Ok(store
+                .entries()
+                .map(|(key, entry)| (key.clone(), entry.clone()))
+                .collect()),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.entries(),
+        }
+    }
+
+    /// Count entries and their sizes per endpoint
+    pub fn stats(&self) -> Result<CacheStats> {
+        let mut stats = CacheStats::default();
+        for (_, entry) in self.entries()? {
+            let size = serde_json::to_vec(&entry).map(|v| v.len()).unwrap_or(0);
+            let endpoint = stats.endpoints.entry(entry.endpoint.clone()).or_default();
+            endpoint.entries += 1;
+            endpoint.size_bytes += size;
+            stats.entries += 1;
+            stats.size_bytes += size;
+            stats.oldest = Some(
+                stats
+                    .oldest
+                    .map_or(entry.timestamp, |t| t.min(entry.timestamp)),
+            );
+            stats.newest = Some(
+                stats
+                    .newest
+                    .map_or(entry.timestamp, |t| t.max(entry.timestamp)),
+            );
+        }
+        stats.documents = Some(match &self.store {
+            Store::Json(store) => store.total_documents(),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.total_documents()?,
+        });
+        Ok(stats)
+    }
+
+    /// Insert or replace an entry, stamped with the current time
+    pub fn set(
+        &mut self,
+        key: &str,
+        endpoint: &str,
+        params: serde_json::Value,
+        response: serde_json::Value,
+    ) -> Result<()> {
+        self.insert(
+            key,
+            CacheEntry {
+                timestamp: now_secs(),
+                endpoint: endpoint.to_string(),
+                params,
+                response,
+            },
+        )
+    }
+
+    fn insert(&mut self, key: &str, entry: CacheEntry) -> Result<()> {
+        match &mut self.store {
+            Store::Json(store) => {
+                store.set(key, entry);
+                Ok(())
+            }
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.set(key, &entry),
+        }
+    }
+
+    /// Remove a single entry
+    pub fn remove(&mut self, key: &str) -> Result<Option<CacheEntry>> {
+        match &mut self.store {
+            Store::Json(store) => Ok(store.remove(key)),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.remove(key),
+        }
+    }
+
+    /// Remove every entry of `endpoint`, returning how many were dropped
+    pub fn invalidate_endpoint(&mut self, endpoint: &str) -> Result<usize> {
+        self.apply(Removal {
+            endpoint: Some(endpoint.to_string()),
+            before: now_secs() + 1,
+        })
+    }
+
+    /// Remove entries written more than `age` ago, returning how many were dropped
+    pub fn prune_older_than(&mut self, age: Duration) -> Result<usize> {
+        self.apply(Removal {
+            endpoint: None,
+            before: now_secs().saturating_sub(age.as_secs()),
+        })
+    }
+
+    /// Remove every entry, returning how many were dropped
+    pub fn clear(&mut self) -> Result<usize> {
+        self.apply(Removal {
+            endpoint: None,
+            before: now_secs() + 1,
+        })
+    }
+
+    fn apply(&mut self, removal: Removal) -> Result<usize> {
+        match &mut self.store {
+            Store::Json(store) => Ok(store.apply(removal)),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.apply(&removal),
+        }
+    }
+
+    /// Copy every entry of the JSON cache at `json_path` into this cache,
+    /// keeping their original timestamps
+    ///
+    /// Stored documents, their tombstones and sync progress are copied as well.
+    pub fn import_json(&mut self, json_path: &str) -> Result<ImportCounts> {
+        let Some(file) = json::load_from_file(json_path) else {
+            anyhow::bail!("No readable JSON cache at {}", json_path);
+        };
+        let entries = file.entries.len();
+        for (key, entry) in file.entries {
+            self.insert(&key, entry)?;
+        }
+        let mut documents = 0;
+        for (account, stored) in file.accounts {
+            let merged: Vec<Document> = stored.documents.into_values().collect();
+            self.merge_documents(&account, &merged)?;
+            let deleted: Vec<String> = stored.deleted.into_keys().collect();
+            documents += merged.len() - self.tombstone_documents(&account, &deleted)?;
+        }
+        for (account, state) in &file.sync {
+            self.set_sync_state(account, state)?;
+        }
+        Ok(ImportCounts { entries, documents })
+    }
+
+    /// Look up a document `account` stored by id
+    pub fn document(&self, account: &str, id: &str) -> Result<Option<Document>> {
+        match &self.store {
+            Store::Json(store) => Ok(store.document(account, id).cloned()),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.document(account, id),
+        }
+    }
+
+    /// Every document `account` stored, in no particular order
+    ///
+    /// Like [`Cache::document`], this skips documents tombstoned by
+    /// [`Cache::tombstone_documents`].
+    pub fn documents(&self, account: &str) -> Result<Vec<Document>> {
+        match &self.store {
+            Store::Json(store) => Ok(store.documents(account).cloned().collect()),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.documents(account),
+        }
+    }
+
+    /// The documents `account` stored that pass the filters of `params` (see
+    /// [`ListDocumentsParams::matches`]), most recently updated first
+    ///
+    /// `html_content` is left out unless `params.with_html_content` is set.
+    /// The SQLite store runs the filters as an indexed query.
+    pub fn find_documents(
+        &self,
+        account: &str,
+        params: &ListDocumentsParams,
+    ) -> Result<Vec<Document>> {
+        let with_html = params.with_html_content == Some(true);
+        match &self.store {
+            Store::Json(store) => {
+                let mut documents: Vec<Document> = store
+                    .documents(account)
+                    .filter(|doc| params.matches(doc))
+                    .cloned()
+                    .collect();
+                documents.sort_by_cached_key(|doc| {
+                    Reverse(doc.updated_at.as_deref().and_then(parse_timestamp))
+                });
+                if !with_html {
+                    for doc in &mut documents {
+                        doc.html_content = None;
+                    }
+                }
+                Ok(documents)
+            }
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.find_documents(account, params, with_html),
+        }
+    }
+
+    /// Ids of every document `account` stored
+    pub fn document_ids(&self, account: &str) -> Result<Vec<String>> {
+        match &self.store {
+            Store::Json(store) => Ok(store.documents(account).map(|doc| doc.id.clone()).collect()),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.document_ids(account),
+        }
+    }
+
+    /// Number of documents `account` stored
+    pub fn document_count(&self, account: &str) -> Result<usize> {
+        match &self.store {
+            Store::Json(store) => Ok(store.documents(account).count()),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.document_count(account),
+        }
+    }
+
+    /// Mark documents `account` stored as deleted in Reader, returning how
+    /// many were marked
+    ///
+    /// Tombstoned documents are kept but hidden from lookups until a later
+    /// [`Cache::merge_documents`] brings them back.
+    pub fn tombstone_documents(&mut self, account: &str, ids: &[String]) -> Result<usize> {
+        let deleted_at = now_secs();
+        match &mut self.store {
+            Store::Json(store) => Ok(store.tombstone_documents(account, ids, deleted_at)),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.tombstone_documents(account, ids, deleted_at),
+        }
+    }
+
+    /// Tombstone the documents `account` stored that are missing from
+    /// `server_ids`, the full list of its ids in Reader, returning their ids
+    /// sorted
+    ///
+    /// Other accounts' documents are left alone, since they never appear in
+    /// this account's list.
+    pub fn reconcile_documents(
+        &mut self,
+        account: &str,
+        server_ids: &HashSet<String>,
+    ) -> Result<Vec<String>> {
+        let mut missing: Vec<String> = self
+            .document_ids(account)?
+            .into_iter()
+            .filter(|id| !server_ids.contains(id))
+            .collect();
+        missing.sort();
+        self.tombstone_documents(account, &missing)?;
+        Ok(missing)
+    }
+
+    /// Insert `documents` into `account`'s document store, replacing stored
+    /// documents with the same id
+    pub fn merge_documents(
+        &mut self,
+        account: &str,
+        documents: &[Document],
+    ) -> Result<MergeCounts> {
+        match &mut self.store {
+            Store::Json(store) => Ok(store.merge_documents(account, documents)),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.merge_documents(account, documents),
+        }
+    }
+
+    /// Sync progress recorded for `account`; empty if it never synced
+    pub fn sync_state(&self, account: &str) -> Result<SyncState> {
+        match &self.store {
+            Store::Json(store) => Ok(store.sync_state(account).cloned().unwrap_or_default()),
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.sync_state(account),
+        }
+    }
+
+    /// Record sync progress for `account`
+    pub fn set_sync_state(&mut self, account: &str, state: &SyncState) -> Result<()> {
+        match &mut self.store {
+            Store::Json(store) => {
+                store.set_sync_state(account, state.clone());
+                Ok(())
+            }
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.set_sync_state(account, state),
+        }
+    }
+}
+
+/// Create the directory `file_path` lives in, if it has one and it's missing
+pub(crate) fn ensure_parent_dir(file_path: &str) -> Result<()> {
+    if let Some(dir) = Path::new(file_path)
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+    {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Cache key for one page of `GET /v3/list/`
+///
+/// Derived from every field of `params` (including the page cursor), so two
+/// queries share a key only if the API would see identical requests.
+/// `account` should be [`ReaderClient::account_id`](crate::ReaderClient::account_id)
+/// so caches shared between tokens or servers never mix their responses.
+pub fn list_cache_key(account: &str, params: &ListDocumentsParams) -> String {
+    let canonical = serde_json::to_string(params).unwrap_or_default();
+    format!("list:{}:{}", account, short_hash(&canonical))
+}
+
+/// Cache key for the full tag list
+pub fn tag_list_cache_key(account: &str) -> String {
+    format!("tag_list:{}", account)
+}
+
+pub(crate) fn short_hash(value: &str) -> String {
+    let digest = Sha256::digest(value.as_bytes());
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(id: &str, updated_at: &str) -> Document {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "source_url": null,
+            "title": null,
+            "updated_at": updated_at,
+        }))
+        .unwrap()
+    }
+
+    /// An empty cache of each backend
+    fn caches(dir: &tempfile::TempDir) -> Vec<Cache> {
+        vec![
+            Cache::new(&dir.path().join("cache.json").to_string_lossy()),
+            #[cfg(feature = "sqlite")]
+            Cache::open_sqlite(&dir.path().join("cache.db").to_string_lossy()).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn documents_are_kept_per_account() {
+        let dir = tempfile::tempdir().unwrap();
+        for mut cache in caches(&dir) {
+            cache
+                .merge_documents("a", &[document("d1", "2024-01-01")])
+                .unwrap();
+            cache
+                .merge_documents("b", &[document("d2", "2024-01-01")])
+                .unwrap();
+
+            assert_eq!(cache.document_ids("a").unwrap(), ["d1"]);
+            assert_eq!(cache.document_count("b").unwrap(), 1);
+            assert!(cache.document("a", "d2").unwrap().is_none());
+            assert!(cache.document("b", "d2").unwrap().is_some());
+            assert_eq!(
+                cache.tombstone_documents("a", &["d2".to_string()]).unwrap(),
+                0
+            );
+            assert_eq!(cache.stats().unwrap().documents, Some(2));
+        }
+    }
+
+    #[test]
+    fn find_documents_filters_and_sorts_on_both_backends() {
+        let documents: Vec<Document> = [
+            ("d1", "later", "2024-01-01T10:00:00+00:00", vec!["rust"]),
+            ("d2", "later", "2024-01-01T13:00:00+02:00", vec![]),
+            (
+                "d3",
+                "archive",
+                "2024-01-01T12:00:00.5+00:00",
+                vec!["rust", "cli"],
+            ),
+        ]
+        .into_iter()
+        .map(|(id, location, updated_at, tags)| {
+            let tags: serde_json::Map<_, _> = tags
+                .into_iter()
+                .map(|tag| (tag.to_string(), serde_json::json!({"name": tag})))
+                .collect();
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "source_url": null,
+                "title": null,
+                "location": location,
+                "updated_at": updated_at,
+                "tags": tags,
+                "html_content": "<p>hi</p>",
+            }))
+            .unwrap()
+        })
+        .collect();
+
+        let dir = tempfile::tempdir().unwrap();
+        for mut cache in caches(&dir) {
+            cache.merge_documents("a", &documents).unwrap();
+            cache
+                .merge_documents("b", &[document("b1", "2024-01-01")])
+                .unwrap();
+            let find = |params: ListDocumentsParams| -> Vec<String> {
+                cache
+                    .find_documents("a", &params)
+                    .unwrap()
+                    .into_iter()
+                    .map(|doc| doc.id)
+                    .collect()
+            };
+
+            // 13:00+02:00 is 11:00 UTC
+            assert_eq!(find(ListDocumentsParams::default()), ["d3", "d2", "d1"]);
+            let location = ListDocumentsParams {
+                location: Some("later".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(find(location), ["d2", "d1"]);
+            let tag = ListDocumentsParams {
+                tag: Some("rust".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(find(tag), ["d3", "d1"]);
+            let after = ListDocumentsParams {
+                updated_after: Some("2024-01-01T12:30:00+01:00".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(find(after), ["d3"]);
+            let id = ListDocumentsParams {
+                id: Some("b1".to_string()),
+                ..Default::default()
+            };
+            assert!(find(id).is_empty());
+
+            let html = |with_html_content| {
+                let params = ListDocumentsParams {
+                    id: Some("d1".to_string()),
+                    with_html_content,
+                    ..Default::default()
+                };
+                cache.find_documents("a", &params).unwrap()[0]
+                    .html_content
+                    .clone()
+            };
+            assert_eq!(html(None), None);
+            assert_eq!(html(Some(true)).as_deref(), Some("<p>hi</p>"));
+        }
+    }
+
+    #[test]
+    fn reconcile_only_tombstones_the_syncing_accounts_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        for mut cache in caches(&dir) {
+            cache
+                .merge_documents(
+                    "a",
+                    &[document("a1", "2024-01-01"), document("a2", "2024-01-01")],
+                )
+                .unwrap();
+            cache
+                .merge_documents("b", &[document("b1", "2024-01-01")])
+                .unwrap();
+
+            let b_ids = HashSet::from(["b1".to_string()]);
+            assert!(cache.reconcile_documents("b", &b_ids).unwrap().is_empty());
+            assert_eq!(cache.document_count("a").unwrap(), 2);
+
+            let a_ids = HashSet::from(["a2".to_string()]);
+            assert_eq!(cache.reconcile_documents("a", &a_ids).unwrap(), ["a1"]);
+            assert_eq!(cache.document_ids("a").unwrap(), ["a2"]);
+            assert_eq!(cache.document_ids("b").unwrap(), ["b1"]);
+        }
+    }
+
+    #[test]
+    fn list_cache_key_covers_account_filters_and_cursor() {
+        let params = ListDocumentsParams {
+            location: Some("later".to_string()),
+            ..Default::default()
+        };
+        let key = list_cache_key("a", &params);
+        assert_eq!(key, list_cache_key("a", &params.clone()));
+        assert!(key.starts_with("list:a:"));
+
+        assert_ne!(key, list_cache_key("b", &params));
+        let archive = ListDocumentsParams {
+            location: Some("archive".to_string()),
+            ..Default::default()
+        };
+        assert_ne!(key, list_cache_key("a", &archive));
+        let next_page = ListDocumentsParams {
+            page_cursor: Some("abc".to_string()),
+            ..params.clone()
+        };
+        assert_ne!(key, list_cache_key("a", &next_page));
+        let with_html = ListDocumentsParams {
+            with_html_content: Some(true),
+            ..params
+        };
+        assert_ne!(key, list_cache_key("a", &with_html));
+
+        assert_ne!(tag_list_cache_key("a"), tag_list_cache_key("b"));
+    }
+
+    #[test]
+    fn expired_and_refreshed_entries_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        for mut cache in caches(&dir) {
+            let entry = |timestamp, endpoint: &str| CacheEntry {
+                timestamp,
+                endpoint: endpoint.to_string(),
+                params: serde_json::Value::Null,
+                response: serde_json::json!({"results": []}),
+            };
+            let old = now_secs() - 3600;
+            cache.insert("old_list", entry(old, "list")).unwrap();
+            cache.insert("old_tags", entry(old, "tag_list")).unwrap();
+            cache
+                .set(
+                    "new_list",
+                    "list",
+                    serde_json::Value::Null,
+                    serde_json::json!({}),
+                )
+                .unwrap();
+
+            cache.set_ttl("list", Duration::from_secs(60));
+            assert!(cache.get("old_list").is_none());
+            assert!(cache.entry("old_list").is_some());
+            assert!(cache.get("new_list").is_some());
+            // Endpoints without a TTL never expire
+            assert!(cache.get("old_tags").is_some());
+
+            cache.set_refresh(true);
+            assert!(cache.get("new_list").is_none());
+            assert!(cache.get("old_tags").is_none());
+            assert!(cache.entry("new_list").is_some());
+        }
+    }
+}
@@ -0,0 +1,680 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Transaction};
+
+use super::{ensure_parent_dir, CacheEntry, MergeCounts, Removal, SyncState};
+use crate::types::{Document, ListDocumentsParams};
+
+const SCHEMA_VERSION: i64 = 3;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS entries (
+    key TEXT PRIMARY KEY,
+    endpoint TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    params TEXT NOT NULL,
+    response TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS entries_endpoint ON entries (endpoint, timestamp);
+
+CREATE TABLE IF NOT EXISTS documents (
+    account TEXT NOT NULL,
+    id TEXT NOT NULL,
+    location TEXT,
+    category TEXT,
+    updated_at TEXT,
+    data TEXT NOT NULL,
+    deleted_at INTEGER,
+    PRIMARY KEY (account, id)
+);
+CREATE INDEX IF NOT EXISTS documents_location ON documents (account, location);
+CREATE INDEX IF NOT EXISTS documents_category ON documents (account, category);
+CREATE INDEX IF NOT EXISTS documents_updated_at ON documents (account, julianday(updated_at));
+
+CREATE TABLE IF NOT EXISTS document_tags (
+    account TEXT NOT NULL,
+    document_id TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (account, document_id, tag),
+    FOREIGN KEY (account, document_id) REFERENCES documents (account, id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS document_tags_tag ON document_tags (account, tag);
+
+CREATE TABLE IF NOT EXISTS sync_state (
+    account TEXT PRIMARY KEY,
+    updated_at TEXT,
+    synced_at INTEGER,
+    reconciled_at INTEGER
+);
+";
+
+/// SQLite store
+///
+/// Responses are kept in `entries`; the document store is the `documents`
+/// table, keyed by account and document id and indexed by location,
+/// category, `updated_at` and tag for [`SqliteStore::find_documents`]. Only
+/// [`SqliteStore::merge_documents`] writes to it, so cached `list` pages never
+/// replace synced documents. Writes go straight to the database, so there is
+/// nothing to merge on save.
+pub(crate) struct SqliteStore {
+    file_path: String,
+    conn: Connection,
+}
+
+impl SqliteStore {
+    pub(crate) fn open(file_path: &str) -> Result<Self> {
+        ensure_parent_dir(file_path)?;
+        let mut conn = Connection::open(file_path)
+            .with_context(|| format!("Failed to open cache database {}", file_path))?;
+        conn.busy_timeout(Duration::from_secs(10))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            anyhow::bail!(
+                "Cache database {} was created by a newer rr (schema version {})",
+                file_path,
+                version
+            );
+        }
+        let tx = conn.transaction()?;
+        if version == 1 {
+            migrate_v1(&tx)?;
+        }
+        if version == 1 || version == 2 {
+            migrate_v2(&tx)?;
+        }
+        tx.execute_batch(SCHEMA)?;
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()?;
+
+        Ok(Self {
+            file_path: file_path.to_string(),
+            conn,
+        })
+    }
+
+    pub(crate) fn file_path(&self) -> &str {
+        &self.file_path
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Result<Option<CacheEntry>> {
+        self.conn
+            .query_row(
+                "SELECT endpoint, timestamp, params, response FROM entries WHERE key = ?1",
+                [key],
+                row_to_entry,
+            )
+            .optional()?
+            .transpose()
+    }
+
+    pub(crate) fn entries(&self) -> Result<Vec<(String, CacheEntry)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT endpoint, timestamp, params, response, key FROM entries")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(4)?, row_to_entry(row)?)))?;
+        rows.map(|row| {
+            let (key, entry) = row?;
+            Ok((key, entry?))
+        })
+        .collect()
+    }
+
+    pub(crate) fn set(&mut self, key: &str, entry: &CacheEntry) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO entries (key, endpoint, timestamp, params, response)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                key,
+                entry.endpoint,
+                entry.timestamp as i64,
+                entry.params.to_string(),
+                entry.response.to_string()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Result<Option<CacheEntry>> {
+        let entry = self.get(key)?;
+        self.conn
+            .execute("DELETE FROM entries WHERE key = ?1", [key])?;
+        Ok(entry)
+    }
+
+    pub(crate) fn apply(&mut self, removal: &Removal) -> Result<usize> {
+        let removed = match &removal.endpoint {
+            Some(endpoint) => self.conn.execute(
+                "DELETE FROM entries WHERE endpoint = ?1 AND timestamp < ?2",
+                params![endpoint, removal.before as i64],
+            )?,
+            None => self.conn.execute(
+                "DELETE FROM entries WHERE timestamp < ?1",
+                [removal.before as i64],
+            )?,
+        };
+        Ok(removed)
+    }
+
+    pub(crate) fn document(&self, account: &str, id: &str) -> Result<Option<Document>> {
+        let data: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT data FROM documents
+                 WHERE account = ?1 AND id = ?2 AND deleted_at IS NULL",
+                [account, id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(data.map(|d| serde_json::from_str(&d)).transpose()?)
+    }
+
+    pub(crate) fn documents(&self, account: &str) -> Result<Vec<Document>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT data FROM documents WHERE account = ?1 AND deleted_at IS NULL")?;
+        let rows = stmt.query_map([account], |row| row.get::<_, String>(0))?;
+        rows.map(|data| Ok(serde_json::from_str(&data?)?)).collect()
+    }
+
+    /// Stored documents passing the filters of `params`, newest first
+    ///
+    /// Timestamps are compared with `julianday`, which reads the offsets and
+    /// fractional seconds Reader returns; an `updated_after` it can't parse
+    /// matches nothing.
+    pub(crate) fn find_documents(
+        &self,
+        account: &str,
+        params: &ListDocumentsParams,
+        with_html: bool,
+    ) -> Result<Vec<Document>> {
+        let mut sql = String::from(if with_html {
+            "SELECT data FROM documents d"
+        } else {
+            "SELECT json_remove(data, '$.html_content') FROM documents d"
+        });
+        sql.push_str(" WHERE account = ? AND deleted_at IS NULL");
+        let mut args = vec![account];
+        if let Some(id) = &params.id {
+            sql.push_str(" AND id = ?");
+            args.push(id);
+        }
+        if let Some(location) = &params.location {
+            sql.push_str(" AND location = ?");
+            args.push(location);
+        }
+        if let Some(category) = &params.category {
+            sql.push_str(" AND category = ?");
+            args.push(category);
+        }
+        if let Some(tag) = &params.tag {
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM document_tags t
+                   WHERE t.account = d.account AND t.document_id = d.id AND t.tag = ?)",
+            );
+            args.push(tag);
+        }
+        if let Some(after) = &params.updated_after {
+            sql.push_str(" AND julianday(updated_at) > julianday(?)");
+            args.push(after);
+        }
+        sql.push_str(" ORDER BY julianday(updated_at) DESC");
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(args), |row| row.get::<_, String>(0))?;
+        rows.map(|data| Ok(serde_json::from_str(&data?)?)).collect()
+    }
+
+    pub(crate) fn document_ids(&self, account: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM documents WHERE account = ?1 AND deleted_at IS NULL")?;
+        let rows = stmt.query_map([account], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub(crate) fn tombstone_documents(
+        &mut self,
+        account: &str,
+        ids: &[String],
+        deleted_at: u64,
+    ) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut count = 0;
+        {
+            let mut mark = tx.prepare_cached(
+                "UPDATE documents SET deleted_at = ?1
+                 WHERE account = ?2 AND id = ?3 AND deleted_at IS NULL",
+            )?;
+            for id in ids {
+                count += mark.execute(params![deleted_at as i64, account, id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    pub(crate) fn merge_documents(
+        &mut self,
+        account: &str,
+        documents: &[Document],
+    ) -> Result<MergeCounts> {
+        let tx = self.conn.transaction()?;
+        let mut counts = MergeCounts::default();
+        {
+            let mut lookup = tx.prepare_cached(
+                "SELECT updated_at FROM documents
+                 WHERE account = ?1 AND id = ?2 AND deleted_at IS NULL",
+            )?;
+            for doc in documents {
+                let stored: Option<Option<String>> = lookup
+                    .query_row([account, &doc.id], |row| row.get(0))
+                    .optional()?;
+                match stored {
+                    None => counts.new += 1,
+                    Some(updated_at) if updated_at != doc.updated_at => counts.changed += 1,
+                    Some(_) => counts.unchanged += 1,
+                }
+            }
+        }
+        upsert_documents(&tx, account, documents)?;
+        tx.commit()?;
+        Ok(counts)
+    }
+
+    pub(crate) fn sync_state(&self, account: &str) -> Result<SyncState> {
+        let state = self
+            .conn
+            .query_row(
+                "SELECT updated_at, synced_at, reconciled_at FROM sync_state WHERE account = ?1",
+                [account],
+                |row| {
+                    Ok(SyncState {
+                        updated_at: row.get(0)?,
+                        synced_at: row.get::<_, Option<i64>>(1)?.map(|t| t as u64),
+                        reconciled_at: row.get::<_, Option<i64>>(2)?.map(|t| t as u64),
+                    })
+                },
+            )
+            .optional()?;
+        Ok(state.unwrap_or_default())
+    }
+
+    pub(crate) fn set_sync_state(&mut self, account: &str, state: &SyncState) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO sync_state (account, updated_at, synced_at, reconciled_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                account,
+                state.updated_at,
+                state.synced_at.map(|t| t as i64),
+                state.reconciled_at.map(|t| t as i64)
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn document_count(&self, account: &str) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM documents WHERE account = ?1 AND deleted_at IS NULL",
+            [account],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    pub(crate) fn total_documents(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM documents WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+}
+
+/// Add the tombstone and reconciliation columns to a version 1 database
+fn migrate_v1(conn: &Connection) -> Result<()> {
+    // Databases from before `rr sync` have no sync state at all
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sync_state (
+             account TEXT PRIMARY KEY,
+             updated_at TEXT,
+             synced_at INTEGER
+         );",
+    )?;
+    for (table, column) in [("documents", "deleted_at"), ("sync_state", "reconciled_at")] {
+        let exists: bool = conn.query_row(
+            &format!(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1",
+                table
+            ),
+            [column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} INTEGER",
+                table, column
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Key the documents of a version 2 database by account
+///
+/// They can only have come from the one account that synced; if several
+/// did, they are dropped along with the sync progress, so the next `rr sync`
+/// of each account fetches its documents again.
+fn migrate_v2(conn: &Connection) -> Result<()> {
+    let accounts: Vec<String> = conn
+        .prepare("SELECT account FROM sync_state")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    conn.execute_batch(
+        "ALTER TABLE document_tags RENAME TO document_tags_v2;
+         ALTER TABLE documents RENAME TO documents_v2;
+         DROP INDEX IF EXISTS documents_location;
+         DROP INDEX IF EXISTS documents_category;
+         DROP INDEX IF EXISTS documents_updated_at;
+         DROP INDEX IF EXISTS document_tags_tag;",
+    )?;
+    conn.execute_batch(SCHEMA)?;
+    match &accounts[..] {
+        [account] => {
+            conn.execute(
+                "INSERT INTO documents (account, id, location, category, updated_at, data, deleted_at)
+                 SELECT ?1, id, location, category, updated_at, data, deleted_at FROM documents_v2",
+                [account],
+            )?;
+            conn.execute(
+                "INSERT INTO document_tags (account, document_id, tag)
+                 SELECT ?1, document_id, tag FROM document_tags_v2",
+                [account],
+            )?;
+        }
+        _ => {
+            conn.execute("DELETE FROM sync_state", [])?;
+        }
+    }
+    conn.execute_batch("DROP TABLE document_tags_v2; DROP TABLE documents_v2;")?;
+    Ok(())
+}
+
+fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<Result<CacheEntry>> {
+    let endpoint: String = row.get(0)?;
+    let timestamp: i64 = row.get(1)?;
+    let params: String = row.get(2)?;
+    let response: String = row.get(3)?;
+    Ok((|| {
+        Ok(CacheEntry {
+            timestamp: timestamp as u64,
+            endpoint,
+            params: serde_json::from_str(&params)?,
+            response: serde_json::from_str(&response)?,
+        })
+    })())
+}
+
+/// Upsert `documents` into `account`'s part of the document table
+fn upsert_documents(tx: &Transaction<'_>, account: &str, documents: &[Document]) -> Result<()> {
+    let mut upsert = tx.prepare_cached(
+        "INSERT OR REPLACE INTO documents (account, id, location, category, updated_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    let mut clear_tags =
+        tx.prepare_cached("DELETE FROM document_tags WHERE account = ?1 AND document_id = ?2")?;
+    let mut insert_tag = tx.prepare_cached(
+        "INSERT OR IGNORE INTO document_tags (account, document_id, tag) VALUES (?1, ?2, ?3)",
+    )?;
+
+    for doc in documents {
+        upsert.execute(params![
+            account,
+            doc.id,
+            doc.location,
+            doc.category,
+            doc.updated_at,
+            serde_json::to_string(doc)?
+        ])?;
+        clear_tags.execute([account, &doc.id])?;
+        for tag in doc.tag_keys() {
+            insert_tag.execute(params![account, doc.id, tag])?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_V2: &str = "
+CREATE TABLE documents (
+    id TEXT PRIMARY KEY,
+    location TEXT,
+    category TEXT,
+    updated_at TEXT,
+    data TEXT NOT NULL,
+    deleted_at INTEGER
+);
+CREATE INDEX documents_location ON documents (location);
+CREATE TABLE document_tags (
+    document_id TEXT NOT NULL REFERENCES documents (id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (document_id, tag)
+);
+CREATE INDEX document_tags_tag ON document_tags (tag);
+CREATE TABLE sync_state (
+    account TEXT PRIMARY KEY,
+    updated_at TEXT,
+    synced_at INTEGER,
+    reconciled_at INTEGER
+);
+PRAGMA user_version = 2;
+";
+
+    fn db_path(dir: &tempfile::TempDir) -> String {
+        dir.path().join("cache.db").to_string_lossy().into_owned()
+    }
+
+    /// A version 2 database holding documents `d1` (tagged) and `d2`
+    /// (tombstoned), synced by `accounts`
+    fn create_v2(path: &str, accounts: &[&str]) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(SCHEMA_V2).unwrap();
+        for (id, deleted_at) in [("d1", None), ("d2", Some(42))] {
+            let data = serde_json::json!({"id": id, "source_url": null, "title": null});
+            conn.execute(
+                "INSERT INTO documents (id, location, data, deleted_at) VALUES (?1, 'later', ?2, ?3)",
+                params![id, data.to_string(), deleted_at],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO document_tags (document_id, tag) VALUES ('d1', 'rust')",
+            [],
+        )
+        .unwrap();
+        for account in accounts {
+            conn.execute(
+                "INSERT INTO sync_state (account, updated_at) VALUES (?1, '2024-01-01')",
+                [account],
+            )
+            .unwrap();
+        }
+    }
+
+    fn document(id: &str, updated_at: &str) -> Document {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "source_url": null,
+            "title": null,
+            "location": "later",
+            "updated_at": updated_at,
+            "tags": {"rust": {"name": "Rust"}},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn tombstoned_documents_come_back_when_merged_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SqliteStore::open(&db_path(&dir)).unwrap();
+        store
+            .merge_documents("a", &[document("d1", "2024-01-01")])
+            .unwrap();
+
+        assert_eq!(
+            store
+                .tombstone_documents("a", &["d1".to_string()], 42)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            store
+                .tombstone_documents("a", &["d1".to_string()], 43)
+                .unwrap(),
+            0
+        );
+        assert!(store.document("a", "d1").unwrap().is_none());
+        assert_eq!(store.document_count("a").unwrap(), 0);
+
+        let counts = store
+            .merge_documents("a", &[document("d1", "2024-01-01")])
+            .unwrap();
+        assert_eq!(counts.new, 1);
+        assert!(store.document("a", "d1").unwrap().is_some());
+        assert_eq!(store.document_ids("a").unwrap(), ["d1"]);
+    }
+
+    #[test]
+    fn newer_schema_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = db_path(&dir);
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+
+        let error = SqliteStore::open(&path).err().unwrap();
+        assert!(error.to_string().contains("newer rr"), "{}", error);
+    }
+
+    #[test]
+    fn migrate_v1_adds_tombstones_and_sync_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = db_path(&dir);
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE documents (
+                     id TEXT PRIMARY KEY, location TEXT, category TEXT,
+                     updated_at TEXT, data TEXT NOT NULL
+                 );
+                 CREATE TABLE document_tags (
+                     document_id TEXT NOT NULL REFERENCES documents (id) ON DELETE CASCADE,
+                     tag TEXT NOT NULL,
+                     PRIMARY KEY (document_id, tag)
+                 );
+                 INSERT INTO documents (id, data)
+                     VALUES ('d1', '{\"id\": \"d1\", \"source_url\": null, \"title\": null}');
+                 PRAGMA user_version = 1;",
+            )
+            .unwrap();
+        }
+
+        let mut store = SqliteStore::open(&path).unwrap();
+        // No account synced, so there's no owner for the old document
+        assert_eq!(store.total_documents().unwrap(), 0);
+        let state = SyncState {
+            reconciled_at: Some(1),
+            ..Default::default()
+        };
+        store.set_sync_state("a", &state).unwrap();
+        assert_eq!(store.sync_state("a").unwrap(), state);
+        let version: i64 = store
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn find_documents_uses_the_indexes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteStore::open(&db_path(&dir)).unwrap();
+        let plan = |sql: &str| -> String {
+            store
+                .conn
+                .prepare(&format!("EXPLAIN QUERY PLAN {}", sql))
+                .unwrap()
+                .query_map([], |row| row.get::<_, String>(3))
+                .unwrap()
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .unwrap()
+                .join("\n")
+        };
+
+        let by_location = plan(
+            "SELECT data FROM documents WHERE account = 'a' AND deleted_at IS NULL
+             AND location = 'later' ORDER BY julianday(updated_at) DESC",
+        );
+        assert!(
+            by_location.starts_with("SEARCH documents USING INDEX"),
+            "{}",
+            by_location
+        );
+        let by_updated = plan(
+            "SELECT data FROM documents WHERE account = 'a' AND deleted_at IS NULL
+             AND julianday(updated_at) > julianday('2024-01-01') ORDER BY julianday(updated_at) DESC",
+        );
+        assert!(
+            by_updated.contains("USING INDEX documents_updated_at (account=? AND <expr>>?)"),
+            "{}",
+            by_updated
+        );
+        let by_tag = plan("SELECT 1 FROM document_tags WHERE account = 'a' AND tag = 'rust'");
+        assert!(by_tag.contains("document_tags_tag"), "{}", by_tag);
+    }
+
+    #[test]
+    fn migrate_v2_gives_documents_to_the_one_synced_account() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = db_path(&dir);
+        create_v2(&path, &["a"]);
+
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.document_ids("a").unwrap(), ["d1"]);
+        assert!(store.document_ids("b").unwrap().is_empty());
+        let tags: Vec<(String, String)> = store
+            .conn
+            .prepare("SELECT account, tag FROM document_tags")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(tags, [("a".to_string(), "rust".to_string())]);
+        assert_eq!(
+            store.sync_state("a").unwrap().updated_at.as_deref(),
+            Some("2024-01-01")
+        );
+    }
+
+    #[test]
+    fn migrate_v2_drops_documents_if_several_accounts_synced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = db_path(&dir);
+        create_v2(&path, &["a", "b"]);
+
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(store.total_documents().unwrap(), 0);
+        assert_eq!(store.sync_state("a").unwrap(), SyncState::default());
+    }
+}
@@ -1,27 +1,118 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
 use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rr::DEFAULT_BASE_URL;
+
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  success
+  1  other error
+  2  invalid arguments or configuration
+  3  authentication failed
+  4  document not found
+  5  validation error
+  6  rate limited
+  7  network error
+  8  unexpected response format
+  9  other API error
+  130  interrupted";
 
 #[derive(Parser, Debug)]
 #[command(name = "rr")]
 #[command(version, about = "Readwise Reader API CLI", long_about = None)]
 #[command(arg_required_else_help = true)]
+#[command(after_help = EXIT_CODES_HELP)]
 pub struct Args {
     /// Readwise API access token
     #[arg(long, env = "READWISE_ACCESS_TOKEN", global = true)]
     pub token: Option<String>,
 
+    /// Readwise API base URL (e.g. a local mock server or caching proxy)
+    #[arg(long, env = "READWISE_BASE_URL", global = true, default_value = DEFAULT_BASE_URL)]
+    pub base_url: String,
+
+    /// Maximum attempts for requests failing with server or network errors (1 disables retries)
+    #[arg(long, global = true, default_value_t = 4)]
+    pub max_attempts: u32,
+
+    /// Initial delay between retries in milliseconds (doubles on each attempt)
+    #[arg(long, global = true, default_value_t = 500)]
+    pub retry_delay_ms: u64,
+
+    /// Maximum total seconds to spend waiting between retries
+    #[arg(long, global = true, default_value_t = 120)]
+    pub retry_max_wait: u64,
+
+    /// Client-side limit in requests per minute for endpoints without their own budget (0 disables)
+    #[arg(long, global = true, default_value_t = 20)]
+    pub rate_limit: u32,
+
+    /// Client-side limit in requests per minute for listing documents (0 disables)
+    #[arg(long, global = true, default_value_t = 20)]
+    pub list_rate_limit: u32,
+
+    /// Client-side limit in requests per minute for creating and updating documents (0 disables)
+    #[arg(long, global = true, default_value_t = 50)]
+    pub write_rate_limit: u32,
+
+    /// Fail instead of waiting once a request has spent this many seconds rate limited
+    #[arg(long, global = true)]
+    pub max_rate_limit_wait: Option<u64>,
+
     /// Enable caching of API responses
-    #[arg(long, global = true, default_value_t = true)]
+    #[arg(
+        long,
+        global = true,
+        default_value_t = true,
+        overrides_with = "no_cache"
+    )]
     pub cache: bool,
 
-    /// Cache file path
-    #[arg(long, global = true, default_value = "./rr_cache.json")]
-    pub cache_file: String,
+    /// Don't cache API responses; offline commands and `sync` need the cache
+    #[arg(long, global = true, overrides_with = "cache")]
+    pub no_cache: bool,
+
+    /// Cache lifetime for every endpoint (e.g. `10m`) or per endpoint
+    /// (e.g. `list=5m,tag_list=1d`) [default: list=5m,tag_list=1h]
+    #[arg(long, global = true, value_delimiter = ',', value_parser = parse_cache_ttl)]
+    pub cache_ttl: Vec<CacheTtl>,
+
+    /// Ignore cached responses but still store the fresh ones
+    #[arg(long, global = true, default_value_t = false)]
+    pub refresh: bool,
+
+    /// Cache storage backend
+    #[arg(long, global = true, value_enum, default_value_t = CacheBackend::Json)]
+    pub cache_backend: CacheBackend,
+
+    /// Cache file path [default: $XDG_CACHE_HOME/rr/rr_cache.json, or
+    /// rr_cache.sqlite3 for sqlite; $XDG_CACHE_HOME defaults to ~/.cache]
+    #[arg(long, global = true)]
+    pub cache_file: Option<String>,
+
+    /// File recording HTTP requests/responses in verbose mode
+    /// [default: $XDG_STATE_HOME/rr/debug_cache.json; $XDG_STATE_HOME defaults
+    /// to ~/.local/state]
+    #[arg(long, global = true)]
+    pub debug_file: Option<PathBuf>,
 
     /// Output raw JSON instead of pretty format
     #[arg(long, global = true, default_value_t = false)]
     pub json: bool,
 
+    /// Answer `list`, `get`, `read`, `export` and `tag-list` from the local
+    /// store (see `rr sync`) and queue `create`/`update`/`delete`, without
+    /// touching the network
+    #[arg(long, global = true, default_value_t = false)]
+    pub offline: bool,
+
+    /// Journal of writes queued offline or after network errors
+    /// [default: $XDG_STATE_HOME/rr/queue.jsonl]
+    #[arg(long, global = true)]
+    pub queue_file: Option<PathBuf>,
+
     /// Enable verbose debug output (prints HTTP requests/responses)
     #[arg(short, long, global = true, default_value_t = false)]
     pub verbose: bool,
@@ -30,6 +121,62 @@ pub struct Args {
     pub command: Command,
 }
 
+/// Endpoints whose responses are cached
+pub const CACHE_ENDPOINTS: &[&str] = &["list", "tag_list"];
+
+/// Cache lifetimes used unless overridden with `--cache-ttl`
+pub const DEFAULT_CACHE_TTLS: &[(&str, Duration)] = &[
+    ("list", Duration::from_secs(5 * 60)),
+    ("tag_list", Duration::from_secs(60 * 60)),
+];
+
+/// One `--cache-ttl` setting; `endpoint` is `None` when it applies to all
+#[derive(Clone, Debug)]
+pub struct CacheTtl {
+    pub endpoint: Option<String>,
+    pub ttl: Duration,
+}
+
+fn parse_cache_ttl(value: &str) -> Result<CacheTtl, String> {
+    match value.split_once('=') {
+        Some((endpoint, ttl)) => {
+            if !CACHE_ENDPOINTS.contains(&endpoint) {
+                return Err(format!(
+                    "unknown endpoint '{}' (expected one of: {})",
+                    endpoint,
+                    CACHE_ENDPOINTS.join(", ")
+                ));
+            }
+            Ok(CacheTtl {
+                endpoint: Some(endpoint.to_string()),
+                ttl: parse_duration(ttl)?,
+            })
+        }
+        None => Ok(CacheTtl {
+            endpoint: None,
+            ttl: parse_duration(value)?,
+        }),
+    }
+}
+
+/// Parse a duration like `90`, `90s`, `15m`, `12h` or `7d` (bare numbers are seconds)
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, multiplier) = match value.char_indices().last() {
+        Some((idx, 's')) => (&value[..idx], 1),
+        Some((idx, 'm')) => (&value[..idx], 60),
+        Some((idx, 'h')) => (&value[..idx], 60 * 60),
+        Some((idx, 'd')) => (&value[..idx], 24 * 60 * 60),
+        _ => (value, 1),
+    };
+    let secs = number
+        .parse::<u64>()
+        .map_err(|_| format!("invalid duration '{}' (e.g. 90s, 15m, 12h, 7d)", value))?
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("duration '{}' is too long", value))?;
+    Ok(Duration::from_secs(secs))
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Check API authentication status
@@ -41,6 +188,12 @@ pub enum Command {
     /// List documents
     List(ListArgs),
 
+    /// Show every field of a single document
+    Get(GetArgs),
+
+    /// Read a document's content as text in the terminal
+    Read(ReadArgs),
+
     /// Update an existing document
     Update(UpdateArgs),
 
@@ -49,65 +202,272 @@ pub enum Command {
 
     /// List all tags
     TagList,
+
+    /// Mirror the library into the local document store, fetching only
+    /// documents updated since the last sync
+    Sync(SyncArgs),
+
+    /// Save every document and tag to a JSON Lines file (gzipped for `.gz`)
+    Backup(BackupArgs),
+
+    /// Recreate the documents in a backup that aren't in Reader
+    Restore(RestoreArgs),
+
+    /// Save the bookmarks in another service's export file
+    Import(ImportArgs),
+
+    /// Write documents to files for use outside Reader
+    #[command(subcommand)]
+    Export(ExportCommand),
+
+    /// Inspect and manage the response cache
+    #[command(subcommand)]
+    Cache(CacheCommand),
+
+    /// Inspect and replay writes queued while offline
+    #[command(subcommand)]
+    Queue(QueueCommand),
+}
+
+impl Command {
+    /// Subcommand name as typed on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Auth => "auth",
+            Command::Create(_) => "create",
+            Command::List(_) => "list",
+            Command::Get(_) => "get",
+            Command::Read(_) => "read",
+            Command::Update(_) => "update",
+            Command::Delete(_) => "delete",
+            Command::TagList => "tag-list",
+            Command::Sync(_) => "sync",
+            Command::Backup(_) => "backup",
+            Command::Restore(_) => "restore",
+            Command::Import(_) => "import",
+            Command::Export(ExportCommand::Markdown(_)) => "export markdown",
+            Command::Cache(_) => "cache",
+            Command::Queue(QueueCommand::List) => "queue list",
+            Command::Queue(QueueCommand::Flush) => "queue flush",
+            Command::Queue(QueueCommand::Clear) => "queue clear",
+        }
+    }
 }
 
 #[derive(clap::Args, Debug)]
+pub struct BackupArgs {
+    /// File to write, e.g. `library.jsonl.gz`
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Include each document's HTML content
+    #[arg(long)]
+    pub with_html_content: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RestoreArgs {
+    /// Backup written by `rr backup`
+    pub file: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// Export file to import
+    pub file: PathBuf,
+
+    /// Format of the export file
+    #[arg(long, value_enum)]
+    pub format: ImportFormat,
+
+    /// Where to record progress, so an interrupted import resumes
+    /// [default: $XDG_STATE_HOME/rr/imports/<hash of the file>.json]
+    #[arg(long)]
+    pub checkpoint: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportCommand {
+    /// Write one Markdown file with YAML front matter per document, updating
+    /// files from earlier exports in place
+    Markdown(ExportMarkdownArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ExportMarkdownArgs {
+    /// Directory to write the Markdown files to
+    #[arg(long)]
+    pub dir: PathBuf,
+
+    #[command(flatten)]
+    pub filter: ListFilterArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum QueueCommand {
+    /// Show queued writes, oldest first
+    List,
+
+    /// Send queued writes in order, stopping at the first network failure
+    Flush,
+
+    /// Discard every queued write
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// Show entry counts, size and age of the cache
+    Stats,
+
+    /// List cache entries
+    List(CacheListArgs),
+
+    /// Show a single cache entry
+    Show(CacheShowArgs),
+
+    /// Remove entries older than a given age
+    Prune(CachePruneArgs),
+
+    /// Remove all entries, or those of one endpoint
+    Clear(CacheClearArgs),
+
+    /// Import the entries of a JSON cache file into the current cache
+    Migrate(CacheMigrateArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CacheListArgs {
+    /// Only list entries of this endpoint
+    #[arg(long, value_enum)]
+    pub endpoint: Option<CacheEndpoint>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CacheShowArgs {
+    /// Cache key (see `rr cache list`)
+    pub key: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CachePruneArgs {
+    /// Age of the entries to remove (e.g. 90s, 15m, 12h, 7d)
+    #[arg(long, value_parser = parse_duration)]
+    pub older_than: Duration,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CacheClearArgs {
+    /// Only clear entries of this endpoint
+    #[arg(long, value_enum)]
+    pub endpoint: Option<CacheEndpoint>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group = ArgGroup::new("bulk").args(["from_file", "stdin"]))]
 pub struct CreateArgs {
-    /// URL of the document (required)
+    /// URL of the document
+    #[arg(long, required_unless_present_any = ["bulk", "file"], conflicts_with = "bulk")]
+    pub url: Option<String>,
+
+    /// Save a local HTML, Markdown (.md) or plain text file as the document,
+    /// titled after its first heading or <title>; without --url, the URL is
+    /// made up from the file's content
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["bulk", "html"])]
+    pub file: Option<PathBuf>,
+
+    /// Save every document listed in a file: one URL per line, or one JSON
+    /// object per line with the fields of a save request
+    #[arg(long, value_name = "FILE")]
+    pub from_file: Option<PathBuf>,
+
+    /// Like --from-file, reading the list from stdin
     #[arg(long)]
-    pub url: String,
+    pub stdin: bool,
+
+    /// Saves in flight at once with --from-file/--stdin (still within the
+    /// rate limit)
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    pub concurrency: u16,
 
     /// HTML content of the document
-    #[arg(long)]
+    #[arg(long, conflicts_with = "bulk")]
     pub html: Option<String>,
 
-    /// Whether to clean the HTML content
-    #[arg(long)]
+    /// Whether to clean the HTML content (with --file, defaults to true for
+    /// HTML files only)
+    #[arg(long, conflicts_with = "bulk")]
     pub should_clean_html: Option<bool>,
 
     /// Document title
-    #[arg(long)]
+    #[arg(long, conflicts_with = "bulk")]
     pub title: Option<String>,
 
     /// Document author
-    #[arg(long)]
+    #[arg(long, conflicts_with = "bulk")]
     pub author: Option<String>,
 
     /// Document summary
-    #[arg(long)]
+    #[arg(long, conflicts_with = "bulk")]
     pub summary: Option<String>,
 
     /// Published date (ISO 8601 format)
-    #[arg(long)]
+    #[arg(long, conflicts_with = "bulk")]
     pub published_date: Option<String>,
 
     /// Image URL for the document
-    #[arg(long)]
+    #[arg(long, conflicts_with = "bulk")]
     pub image_url: Option<String>,
 
-    /// Location: new, later, archive, feed
+    /// Location: new, later, archive, feed (with --from-file/--stdin, the
+    /// default for lines that don't set one)
     #[arg(long, value_enum)]
     pub location: Option<Location>,
 
     /// Category: article, email, rss, highlight, note, pdf, epub, tweet, video
+    /// (a default, like --location)
     #[arg(long, value_enum)]
     pub category: Option<Category>,
 
-    /// Application that saved the document
+    /// Application that saved the document (a default, like --location)
     #[arg(long)]
     pub saved_using: Option<String>,
 
-    /// Tags (comma-separated)
+    /// Tags (comma-separated), added to each line's own tags with
+    /// --from-file/--stdin
     #[arg(long, value_delimiter = ',')]
     pub tags: Option<Vec<String>>,
 
     /// Notes for the document
-    #[arg(long)]
+    #[arg(long, conflicts_with = "bulk")]
     pub notes: Option<String>,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct ListArgs {
+    #[command(flatten)]
+    pub filter: ListFilterArgs,
+
+    /// Pagination cursor
+    #[arg(long)]
+    pub cursor: Option<String>,
+
+    /// Include HTML content in response
+    #[arg(long)]
+    pub with_html_content: Option<bool>,
+
+    /// Include raw source URL in response
+    #[arg(long)]
+    pub with_raw_source_url: Option<bool>,
+
+    /// Fetch all pages without waiting for user input
+    #[arg(long, short)]
+    pub all: bool,
+}
+
+/// Filters shared by commands that select documents like `list`
+#[derive(clap::Args, Debug)]
+pub struct ListFilterArgs {
     /// Fetch specific document by ID
     #[arg(long)]
     pub id: Option<String>,
@@ -127,22 +487,30 @@ pub struct ListArgs {
     /// Filter by tag
     #[arg(long)]
     pub tag: Option<String>,
+}
 
-    /// Pagination cursor
-    #[arg(long)]
-    pub cursor: Option<String>,
+#[derive(clap::Args, Debug)]
+pub struct GetArgs {
+    /// Document ID
+    pub id: String,
 
-    /// Include HTML content in response
+    /// Include the document's HTML content
     #[arg(long)]
-    pub with_html_content: Option<bool>,
+    pub with_html_content: bool,
+}
 
-    /// Include raw source URL in response
+#[derive(clap::Args, Debug)]
+pub struct ReadArgs {
+    /// Document ID
+    pub id: String,
+
+    /// Wrap text at this many columns (defaults to $COLUMNS, or 80)
     #[arg(long)]
-    pub with_raw_source_url: Option<bool>,
+    pub width: Option<usize>,
 
-    /// Fetch all pages without waiting for user input
-    #[arg(long, short)]
-    pub all: bool,
+    /// Print to stdout instead of piping through $PAGER
+    #[arg(long)]
+    pub no_pager: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -193,6 +561,26 @@ pub struct DeleteArgs {
     pub id: String,
 }
 
+#[derive(clap::Args, Debug)]
+pub struct SyncArgs {
+    /// Ignore the last sync and fetch every document again
+    #[arg(long)]
+    pub full: bool,
+
+    /// Store each document's HTML content too
+    #[arg(long)]
+    pub with_html_content: bool,
+
+    /// How often to check local documents against the full list of ids in
+    /// Reader and tombstone those deleted there (e.g. 12h, 7d; 0 for every sync)
+    #[arg(long, value_parser = parse_duration, default_value = "7d")]
+    pub reconcile_every: Duration,
+
+    /// Reconcile now, however recently it last happened
+    #[arg(long)]
+    pub reconcile: bool,
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 pub enum Location {
     New,
@@ -233,6 +621,65 @@ impl fmt::Display for ListLocation {
     }
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ImportFormat {
+    /// Pocket export: `part_000000.csv` or the older `ril_export.html`
+    Pocket,
+    /// Instapaper CSV export
+    Instapaper,
+    /// Pinboard JSON export
+    Pinboard,
+    /// Netscape bookmark file, as exported by browsers and most services
+    NetscapeHtml,
+    /// CSV with a header row naming `url` and any of `title`, `author`,
+    /// `summary`, `tags`, `location`, `category`, `published_date`, `notes`
+    /// and `saved_at`
+    Csv,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CacheMigrateArgs {
+    /// JSON cache file to import [default: $XDG_CACHE_HOME/rr/rr_cache.json]
+    #[arg(long)]
+    pub from: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheBackend {
+    /// Single JSON file
+    Json,
+    /// SQLite database, also storing documents as indexed rows
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+impl CacheBackend {
+    /// Name of the cache file inside the cache directory
+    pub fn file_name(self) -> &'static str {
+        match self {
+            CacheBackend::Json => "rr_cache.json",
+            #[cfg(feature = "sqlite")]
+            CacheBackend::Sqlite => "rr_cache.sqlite3",
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CacheEndpoint {
+    List,
+    #[value(name = "tag_list")]
+    TagList,
+}
+
+impl fmt::Display for CacheEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheEndpoint::List => write!(f, "list"),
+            CacheEndpoint::TagList => write!(f, "tag_list"),
+        }
+    }
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 pub enum Category {
     Article,
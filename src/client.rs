@@ -1,14 +1,20 @@
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::time::{sleep, Duration};
 
 use anyhow::Result;
 
+use crate::cache::short_hash;
+use crate::error::ReaderError;
+use crate::rate_limit::{parse_retry_after, Budget, RateLimiter, MAX_RETRY_AFTER_SECS};
+use crate::retry::RetryPolicy;
 use crate::types::*;
 
 /// Parse retry seconds from API error response body
@@ -39,20 +45,63 @@ fn extract_seconds_from_message(msg: &str) -> Option<u64> {
     None
 }
 
-/// Display countdown on same line and wait
-async fn countdown_wait(seconds: u64) {
-    for remaining in (1..=seconds).rev() {
-        eprint!("\rRate limited. Retrying in {} seconds...  ", remaining);
-        std::io::stderr().flush().ok();
-        sleep(Duration::from_secs(1)).await;
+/// How rate-limit waits are reported on stderr
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WaitNotice {
+    /// Countdown on a single updating line when stderr is a terminal,
+    /// one plain line per wait otherwise
+    #[default]
+    Auto,
+    /// One JSON event object per line, for wrappers to parse
+    Json,
+}
+
+/// Report a rate-limit wait on stderr and sleep through it
+async fn countdown_wait(seconds: u64, url: &str, notice: WaitNotice) {
+    match notice {
+        WaitNotice::Json => {
+            let event = serde_json::json!({
+                "event": "rate_limit_wait",
+                "url": url,
+                "wait_seconds": seconds,
+            });
+            eprintln!("{}", event);
+            sleep(Duration::from_secs(seconds)).await;
+        }
+        WaitNotice::Auto if !std::io::stderr().is_terminal() => {
+            eprintln!(
+                "Rate limited on {}. Retrying in {} seconds...",
+                url, seconds
+            );
+            sleep(Duration::from_secs(seconds)).await;
+        }
+        WaitNotice::Auto => {
+            for remaining in (1..=seconds).rev() {
+                eprint!("\rRate limited. Retrying in {} seconds...  ", remaining);
+                std::io::stderr().flush().ok();
+                sleep(Duration::from_secs(1)).await;
+            }
+            eprint!("\rRetrying now...                           \n");
+            std::io::stderr().flush().ok();
+        }
     }
-    eprint!("\rRetrying now...                           \n");
-    std::io::stderr().flush().ok();
 }
 
-const BASE_URL: &str = "https://readwise.io/api";
-const DEBUG_CACHE_FILE: &str = "debug_cache.json";
+/// Base URL of the hosted Readwise API
+pub const DEFAULT_BASE_URL: &str = "https://readwise.io/api";
+
+/// Send a request and read the full response body
+async fn send_request(
+    request: reqwest::RequestBuilder,
+) -> std::result::Result<(StatusCode, HeaderMap, String), reqwest::Error> {
+    let response = request.send().await?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let text = response.text().await?;
+    Ok((status, headers, text))
+}
 
+/// A single recorded HTTP exchange (verbose mode only)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DebugEntry {
     pub timestamp: String,
@@ -63,22 +112,33 @@ pub struct DebugEntry {
     pub response_body: Option<serde_json::Value>,
 }
 
+/// Log of HTTP exchanges persisted to a JSON file in verbose mode
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct DebugCache {
+    #[serde(skip)]
+    file_path: PathBuf,
     pub entries: Vec<DebugEntry>,
 }
 
 impl DebugCache {
-    pub fn new() -> Self {
+    /// Create a debug log backed by `path`, appending to any entries already in it
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let file_path = path.into();
         // Load existing entries from file if it exists
-        if let Ok(content) = fs::read_to_string(DEBUG_CACHE_FILE) {
-            if let Ok(cache) = serde_json::from_str::<DebugCache>(&content) {
-                return cache;
-            }
-        }
-        Self { entries: vec![] }
+        let entries = fs::read_to_string(&file_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<DebugCache>(&content).ok())
+            .map(|cache| cache.entries)
+            .unwrap_or_default();
+        Self { file_path, entries }
     }
 
+    /// The file this log is saved to
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Record one request/response pair
     pub fn add_entry(
         &mut self,
         method: &str,
@@ -101,61 +161,134 @@ impl DebugCache {
         self.entries.push(entry);
     }
 
+    /// Write the log to its file, creating the parent directory if needed
     pub fn save(&self) -> Result<()> {
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(DEBUG_CACHE_FILE, content)?;
-        Ok(())
-    }
-
-    /// Try to save the debug cache if the file exists
-    /// Used by signal handlers to save cache on interrupt/panic
-    pub fn save_if_exists() -> Result<()> {
-        let path = Path::new(DEBUG_CACHE_FILE);
-        if !path.exists() {
-            // No debug cache file exists yet, nothing to save
-            return Ok(());
+        if let Some(dir) = self
+            .file_path
+            .parent()
+            .filter(|d| !d.as_os_str().is_empty())
+        {
+            fs::create_dir_all(dir)?;
         }
-
-        // Load and save the debug cache to persist any in-memory changes
-        let cache = Self::new();
-        cache.save()
+        fs::write(&self.file_path, content)?;
+        Ok(())
     }
 }
 
+/// Client for the Readwise Reader API
+///
+/// Requests are paced by a client-side [`RateLimiter`], and HTTP 429 responses
+/// are retried automatically after the wait the server asks for;
+/// server errors and transport failures are retried according to a [`RetryPolicy`].
+///
+/// Cloning is cheap: clones share the connection pool, rate limiter and debug
+/// log, so requests can run concurrently on clones without exceeding the limits.
+#[derive(Clone)]
 pub struct ReaderClient {
     client: reqwest::Client,
+    base_url: String,
+    account_id: String,
     verbose: bool,
-    debug_cache: Option<DebugCache>,
+    debug_cache: Option<Arc<Mutex<DebugCache>>>,
+    retry_policy: RetryPolicy,
+    rate_limiter: RateLimiter,
+    wait_notice: WaitNotice,
+    max_rate_limit_wait: Option<Duration>,
 }
 
 impl ReaderClient {
-    pub fn new(token: &str, verbose: bool) -> Result<Self> {
+    /// Create a client authenticating with `token` against `base_url`
+    /// (usually [`DEFAULT_BASE_URL`]). With `verbose`, requests and responses
+    /// are echoed to stderr; see [`ReaderClient::with_debug_file`] to also
+    /// record them in a [`DebugCache`].
+    pub fn new(token: &str, base_url: &str, verbose: bool) -> Result<Self, ReaderError> {
         let mut headers = HeaderMap::new();
         let auth_value = format!("Token {}", token);
-        headers.insert(AUTHORIZATION, HeaderValue::from_str(&auth_value)?);
+        let auth_header = HeaderValue::from_str(&auth_value)
+            .map_err(|_| ReaderError::InvalidConfig("token contains invalid characters".into()))?;
+        headers.insert(AUTHORIZATION, auth_header);
 
         let client = reqwest::Client::builder()
             .default_headers(headers)
-            .build()?;
-
-        let debug_cache = if verbose {
-            Some(DebugCache::new())
-        } else {
-            None
-        };
+            .build()
+            .map_err(|e| ReaderError::InvalidConfig(e.to_string()))?;
 
         Ok(Self {
             client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            account_id: short_hash(&format!("{}\n{}", base_url, token)),
             verbose,
-            debug_cache,
+            debug_cache: None,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: RateLimiter::default(),
+            wait_notice: WaitNotice::default(),
+            max_rate_limit_wait: None,
         })
     }
 
+    /// Record every request and response in the [`DebugCache`] at `path`
+    pub fn with_debug_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.debug_cache = Some(Arc::new(Mutex::new(DebugCache::new(path))));
+        self
+    }
+
+    /// Replace the default [`RetryPolicy`] for server and transport errors
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Replace the default [`RateLimiter`], e.g. to share one across clients
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Choose how rate-limit waits are reported on stderr
+    pub fn with_wait_notice(mut self, wait_notice: WaitNotice) -> Self {
+        self.wait_notice = wait_notice;
+        self
+    }
+
+    /// Give up with [`ReaderError::RateLimited`] instead of waiting once a
+    /// single request would spend more than `max_wait` waiting out HTTP 429s
+    pub fn with_max_rate_limit_wait(mut self, max_wait: Option<Duration>) -> Self {
+        self.max_rate_limit_wait = max_wait;
+        self
+    }
+
+    /// The limiter this client draws from; clone it to share the budgets
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Stable, opaque identifier for the token and server this client uses
+    ///
+    /// Safe to persist (the token can't be recovered from it); used to keep
+    /// cache entries of different accounts apart.
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// The live debug log, if one was configured
+    ///
+    /// Shared with the client, so it can be saved from elsewhere (e.g. a
+    /// signal handler) with everything recorded so far.
+    pub fn debug_cache(&self) -> Option<&Arc<Mutex<DebugCache>>> {
+        self.debug_cache.as_ref()
+    }
+
+    /// Persist the debug log, if one was configured
     pub fn save_debug_cache(&self) -> Result<()> {
         if let Some(cache) = &self.debug_cache {
+            let cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
             cache.save()?;
             if self.verbose {
-                eprintln!("[DEBUG] Saved debug cache to {}", DEBUG_CACHE_FILE);
+                eprintln!(
+                    "[DEBUG] Saved debug cache to {}",
+                    cache.file_path().display()
+                );
             }
         }
         Ok(())
@@ -189,7 +322,8 @@ impl ReaderClient {
             }
         }
 
-        if let Some(cache) = &mut self.debug_cache {
+        if let Some(cache) = &self.debug_cache {
+            let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
             cache.add_entry(
                 method,
                 url,
@@ -208,47 +342,138 @@ impl ReaderClient {
         &mut self,
         method: &str,
         url: &str,
+        budget: Budget,
         build_request: B,
         check_success: S,
         parse_response: P,
-    ) -> Result<T>
+    ) -> Result<T, ReaderError>
     where
         B: Fn(&reqwest::Client) -> (reqwest::RequestBuilder, Option<String>),
         S: Fn(StatusCode) -> bool,
-        P: Fn(StatusCode, String) -> Result<T>,
+        P: Fn(StatusCode, String) -> serde_json::Result<T>,
     {
+        let mut attempt = 1;
+        let mut retry_waited = Duration::ZERO;
+        let mut rate_limit_waited = Duration::ZERO;
+
         loop {
             let (request, request_body) = build_request(&self.client);
             let request_body_ref = request_body.as_deref();
 
+            self.rate_limiter.acquire(budget).await;
             self.log_request(method, url, request_body_ref);
 
-            let response = request.send().await?;
-            let status = response.status();
+            let (status, headers, text) = match send_request(request).await {
+                Ok(response) => response,
+                Err(source) => {
+                    if RetryPolicy::is_retryable_error(method, &source)
+                        && self
+                            .backoff(&mut attempt, &mut retry_waited, url, &source.to_string())
+                            .await
+                    {
+                        continue;
+                    }
+                    return Err(ReaderError::Network {
+                        url: url.to_string(),
+                        source,
+                    });
+                }
+            };
+            self.log_response(method, url, request_body_ref, status, &text);
 
             if check_success(status) {
-                let text = response.text().await?;
-                self.log_response(method, url, request_body_ref, status, &text);
-                return parse_response(status, text);
+                return parse_response(status, text).map_err(|source| ReaderError::Decode {
+                    url: url.to_string(),
+                    source,
+                });
             } else if status == StatusCode::TOO_MANY_REQUESTS {
-                let text = response.text().await.unwrap_or_default();
-                self.log_response(method, url, request_body_ref, status, &text);
-                let wait_secs = parse_retry_seconds(&text).unwrap_or(60);
-                countdown_wait(wait_secs).await;
+                let wait_secs = parse_retry_after(&headers)
+                    .or_else(|| parse_retry_seconds(&text))
+                    .unwrap_or(60)
+                    .min(MAX_RETRY_AFTER_SECS);
+                let wait = Duration::from_secs(wait_secs);
+
+                rate_limit_waited += wait;
+                if self
+                    .max_rate_limit_wait
+                    .is_some_and(|max| rate_limit_waited > max)
+                {
+                    if self.wait_notice == WaitNotice::Json {
+                        let event = serde_json::json!({
+                            "event": "rate_limit_exceeded",
+                            "url": url,
+                            "retry_after_seconds": wait_secs,
+                        });
+                        eprintln!("{}", event);
+                    }
+                    return Err(ReaderError::RateLimited {
+                        url: url.to_string(),
+                        retry_after: Some(wait_secs),
+                    });
+                }
+                self.rate_limiter.pause(budget, wait);
+                countdown_wait(wait_secs, url, self.wait_notice).await;
+            } else if RetryPolicy::is_retryable_status(method, status)
+                && self
+                    .backoff(
+                        &mut attempt,
+                        &mut retry_waited,
+                        url,
+                        &format!("HTTP {}", status),
+                    )
+                    .await
+            {
+                continue;
             } else {
-                let text = response.text().await.unwrap_or_default();
-                self.log_response(method, url, request_body_ref, status, &text);
-                anyhow::bail!("API request failed: HTTP {}: {}", status, text);
+                return Err(ReaderError::from_response(url, status, text));
             }
         }
     }
 
-    pub async fn check_auth(&mut self) -> Result<bool> {
-        let url = format!("{}/v2/auth/", BASE_URL);
+    /// Sleep before the next attempt if the retry policy allows one
+    async fn backoff(
+        &self,
+        attempt: &mut u32,
+        waited: &mut Duration,
+        url: &str,
+        reason: &str,
+    ) -> bool {
+        let Some(delay) = self.retry_policy.delay(*attempt, *waited) else {
+            return false;
+        };
+
+        *attempt += 1;
+        *waited += delay;
+        if self.wait_notice == WaitNotice::Json {
+            let event = serde_json::json!({
+                "event": "retry",
+                "url": url,
+                "reason": reason,
+                "wait_seconds": delay.as_secs_f64(),
+                "attempt": *attempt,
+                "max_attempts": self.retry_policy.max_attempts,
+            });
+            eprintln!("{}", event);
+        } else {
+            eprintln!(
+                "Request failed ({}). Retrying in {:.1} seconds (attempt {}/{})...",
+                reason,
+                delay.as_secs_f64(),
+                attempt,
+                self.retry_policy.max_attempts
+            );
+        }
+        sleep(delay).await;
+        true
+    }
+
+    pub async fn check_auth(&mut self) -> Result<bool, ReaderError> {
+        let url = format!("{}/v2/auth/", self.base_url);
 
         self.execute_request(
             "GET",
             &url,
+            Budget::Default,
             |client| (client.get(&url), None),
             |status| status == StatusCode::NO_CONTENT || status == StatusCode::UNAUTHORIZED,
             |status, _text| {
@@ -262,21 +487,23 @@ impl ReaderClient {
         .await
     }
 
+    /// Save a new document (`POST /v3/save/`)
     pub async fn create_document(
         &mut self,
         request: CreateDocumentRequest,
-    ) -> Result<CreateDocumentResponse> {
-        let url = format!("{}/v3/save/", BASE_URL);
+    ) -> Result<CreateDocumentResponse, ReaderError> {
+        let url = format!("{}/v3/save/", self.base_url);
 
         self.execute_request(
             "POST",
             &url,
+            Budget::Write,
             |client| {
                 let body = serde_json::to_string(&request).unwrap_or_default();
                 (client.post(&url).json(&request), Some(body))
             },
             |status| status.is_success(),
-            |_status, text| Ok(serde_json::from_str(&text)?),
+            |_status, text| serde_json::from_str(&text),
         )
         .await
     }
@@ -312,11 +539,14 @@ impl ReaderClient {
         query
     }
 
+    /// Fetch a single page of documents (`GET /v3/list/`)
+    ///
+    /// Set `params.page_cursor` to the previous page's `next_page_cursor` to continue.
     pub async fn list_documents(
         &mut self,
         params: &ListDocumentsParams,
-    ) -> Result<ListDocumentsResponse> {
-        let url = format!("{}/v3/list/", BASE_URL);
+    ) -> Result<ListDocumentsResponse, ReaderError> {
+        let url = format!("{}/v3/list/", self.base_url);
         let query_params = Self::build_list_query_params(params);
 
         // Build full URL for logging
@@ -334,6 +564,7 @@ impl ReaderClient {
         self.execute_request(
             "GET",
             &full_url,
+            Budget::List,
             |client| {
                 let mut request = client.get(&url);
                 for (key, value) in &query_params {
@@ -342,37 +573,65 @@ impl ReaderClient {
                 (request, None)
             },
             |status| status.is_success(),
-            |_status, text| Ok(serde_json::from_str(&text)?),
+            |_status, text| serde_json::from_str(&text),
         )
         .await
     }
 
+    /// Fetch a single document by ID (`GET /v3/list/?id=<id>`)
+    ///
+    /// Fails with [`ReaderError::NotFound`] if there is no such document.
+    pub async fn get_document(
+        &mut self,
+        id: &str,
+        with_html_content: bool,
+    ) -> Result<Document, ReaderError> {
+        let params = ListDocumentsParams {
+            id: Some(id.to_string()),
+            with_html_content: with_html_content.then_some(true),
+            ..ListDocumentsParams::default()
+        };
+        let response = self.list_documents(&params).await?;
+        response
+            .results
+            .into_iter()
+            .find(|doc| doc.id == id)
+            .ok_or_else(|| ReaderError::NotFound {
+                url: format!("{}/v3/list/?id={}", self.base_url, id),
+                detail: Some(format!("no document with ID {}", id)),
+            })
+    }
+
+    /// Update fields of an existing document (`PATCH /v3/update/<id>/`)
     pub async fn update_document(
         &mut self,
         id: &str,
         request: UpdateDocumentRequest,
-    ) -> Result<Document> {
-        let url = format!("{}/v3/update/{}/", BASE_URL, id);
+    ) -> Result<Document, ReaderError> {
+        let url = format!("{}/v3/update/{}/", self.base_url, id);
 
         self.execute_request(
             "PATCH",
             &url,
+            Budget::Write,
             |client| {
                 let body = serde_json::to_string(&request).unwrap_or_default();
                 (client.patch(&url).json(&request), Some(body))
             },
             |status| status.is_success(),
-            |_status, text| Ok(serde_json::from_str(&text)?),
+            |_status, text| serde_json::from_str(&text),
         )
         .await
     }
 
-    pub async fn delete_document(&mut self, id: &str) -> Result<()> {
-        let url = format!("{}/v3/delete/{}/", BASE_URL, id);
+    /// Delete a document (`DELETE /v3/delete/<id>/`)
+    pub async fn delete_document(&mut self, id: &str) -> Result<(), ReaderError> {
+        let url = format!("{}/v3/delete/{}/", self.base_url, id);
 
         self.execute_request(
             "DELETE",
             &url,
+            Budget::Default,
             |client| (client.delete(&url), None),
             |status| status == StatusCode::NO_CONTENT,
             |_status, _text| Ok(()),
@@ -380,44 +639,93 @@ impl ReaderClient {
         .await
     }
 
-    pub async fn list_all_tags(&mut self) -> Result<Vec<Tag>> {
-        let mut all_tags = Vec::new();
-        let mut cursor: Option<String> = None;
+    /// Fetch a single page of tags (`GET /v3/tags/`)
+    pub async fn list_tags(
+        &mut self,
+        page_cursor: Option<&str>,
+    ) -> Result<ListTagsResponse, ReaderError> {
+        let url = format!("{}/v3/tags/", self.base_url);
+        let full_url = if let Some(c) = page_cursor {
+            format!("{}?pageCursor={}", url, c)
+        } else {
+            url.clone()
+        };
 
-        loop {
-            let url = format!("{}/v3/tags/", BASE_URL);
-            let full_url = if let Some(c) = &cursor {
-                format!("{}?pageCursor={}", url, c)
-            } else {
-                url.clone()
-            };
+        self.execute_request(
+            "GET",
+            &full_url,
+            Budget::Default,
+            |client| {
+                let mut request = client.get(&url);
+                if let Some(c) = page_cursor {
+                    request = request.query(&[("pageCursor", c)]);
+                }
+                (request, None)
+            },
+            |status| status.is_success(),
+            |_status, text| serde_json::from_str(&text),
+        )
+        .await
+    }
 
-            let result: ListTagsResponse = self
-                .execute_request(
-                    "GET",
-                    &full_url,
-                    |client| {
-                        let mut request = client.get(&url);
-                        if let Some(c) = &cursor {
-                            request = request.query(&[("pageCursor", c)]);
-                        }
-                        (request, None)
-                    },
-                    |status| status.is_success(),
-                    |_status, text| Ok(serde_json::from_str(&text)?),
-                )
-                .await?;
-
-            for tag in result.results {
-                all_tags.push(tag);
-            }
+    /// Fetch every tag, following pagination
+    pub async fn list_all_tags(&mut self) -> Result<Vec<Tag>, ReaderError> {
+        self.tags_stream().try_collect().await
+    }
 
-            match result.next_page_cursor {
-                Some(next_cursor) => cursor = Some(next_cursor),
-                None => break,
-            }
-        }
+    /// Stream every document matching `params`, following `nextPageCursor`
+    /// lazily: the next page is only requested once the current one has been
+    /// consumed, so dropping the stream early saves requests.
+    ///
+    /// ```no_run
+    /// # use futures::{StreamExt, TryStreamExt};
+    /// # use rr::{ListDocumentsParams, ReaderClient};
+    /// # async fn example(client: &mut ReaderClient) -> Result<(), rr::ReaderError> {
+    /// let recent: Vec<_> = client
+    ///     .documents_stream(ListDocumentsParams::default())
+    ///     .take(50)
+    ///     .try_collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn documents_stream(
+        &mut self,
+        params: ListDocumentsParams,
+    ) -> BoxStream<'_, Result<Document, ReaderError>> {
+        stream::try_unfold((self, Some(params)), |(client, params)| async move {
+            let Some(mut params) = params else {
+                return Ok(None);
+            };
+            let page = client.list_documents(&params).await?;
+            let next = page.next_page_cursor.map(|cursor| {
+                params.page_cursor = Some(cursor);
+                params
+            });
+            Ok(Some((page.results, (client, next))))
+        })
+        .map_ok(|documents| stream::iter(documents.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+    }
 
-        Ok(all_tags)
+    /// Stream every tag, following `nextPageCursor` lazily
+    pub fn tags_stream(&mut self) -> BoxStream<'_, Result<Tag, ReaderError>> {
+        stream::try_unfold(
+            (self, Some(None::<String>)),
+            |(client, cursor)| async move {
+                let Some(cursor) = cursor else {
+                    return Ok(None);
+                };
+                let page = client.list_tags(cursor.as_deref()).await?;
+                Ok(Some((
+                    page.results,
+                    (client, page.next_page_cursor.map(Some)),
+                )))
+            },
+        )
+        .map_ok(|tags| stream::iter(tags.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
     }
 }
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+/// Errors returned by [`ReaderClient`](crate::ReaderClient)
+#[derive(Debug, Error)]
+pub enum ReaderError {
+    /// The token was rejected (HTTP 401/403)
+    #[error("Authentication failed: HTTP {status} for {url}{}", describe(.detail, None))]
+    Unauthorized {
+        url: String,
+        status: u16,
+        detail: Option<String>,
+    },
+
+    /// The requested document or endpoint does not exist (HTTP 404)
+    #[error("Not found: {url}{}", describe(.detail, None))]
+    NotFound { url: String, detail: Option<String> },
+
+    /// The request was rejected as invalid (HTTP 400/422)
+    #[error("Validation failed: HTTP {status} for {url}{}", describe(.detail, Some(.field_errors)))]
+    Validation {
+        url: String,
+        status: u16,
+        detail: Option<String>,
+        /// Per-field messages, e.g. `{"url": ["Enter a valid URL."]}`
+        field_errors: BTreeMap<String, Vec<String>>,
+    },
+
+    /// The server kept rate limiting the request (HTTP 429) beyond the
+    /// client's maximum rate-limit wait
+    #[error("Rate limited: {url}{}", .retry_after.map(|s| format!(" (retry after {}s)", s)).unwrap_or_default())]
+    RateLimited {
+        url: String,
+        retry_after: Option<u64>,
+    },
+
+    /// Any other unsuccessful HTTP status
+    #[error("API request failed: HTTP {status} for {url}{}", describe(.detail, None))]
+    Api {
+        url: String,
+        status: u16,
+        detail: Option<String>,
+        body: String,
+    },
+
+    /// The request could not be sent or the response could not be read
+    #[error("Network error for {url}: {source}")]
+    Network {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// The response body was not the expected JSON
+    #[error("Failed to decode response from {url}: {source}")]
+    Decode {
+        url: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// The client could not be constructed (e.g. the token is not a valid header value)
+    #[error("Invalid client configuration: {0}")]
+    InvalidConfig(String),
+}
+
+impl ReaderError {
+    /// Build the error for an unsuccessful response, parsing Reader's JSON error body
+    pub(crate) fn from_response(url: &str, status: reqwest::StatusCode, body: String) -> Self {
+        let (detail, field_errors) = parse_error_body(&body);
+        let url = url.to_string();
+        match status.as_u16() {
+            401 | 403 => ReaderError::Unauthorized {
+                url,
+                status: status.as_u16(),
+                detail,
+            },
+            404 => ReaderError::NotFound { url, detail },
+            400 | 422 => ReaderError::Validation {
+                url,
+                status: status.as_u16(),
+                detail,
+                field_errors,
+            },
+            _ => ReaderError::Api {
+                url,
+                status: status.as_u16(),
+                detail,
+                body,
+            },
+        }
+    }
+
+    /// HTTP status of the failed response, if the server answered
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ReaderError::Unauthorized { status, .. }
+            | ReaderError::Validation { status, .. }
+            | ReaderError::Api { status, .. } => Some(*status),
+            ReaderError::NotFound { .. } => Some(404),
+            ReaderError::RateLimited { .. } => Some(429),
+            ReaderError::Network { .. }
+            | ReaderError::Decode { .. }
+            | ReaderError::InvalidConfig(_) => None,
+        }
+    }
+
+    /// Whether the same request may succeed later: network failures, rate
+    /// limiting and server errors
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ReaderError::Network { .. } | ReaderError::RateLimited { .. } => true,
+            ReaderError::Api { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Whether a bulk command should stop at this error rather than record
+    /// it and move on: later requests would fail the same way
+    pub fn aborts_batch(&self) -> bool {
+        self.is_transient() || matches!(self, ReaderError::Unauthorized { .. })
+    }
+
+    /// URL of the failed request, if any
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            ReaderError::Unauthorized { url, .. }
+            | ReaderError::NotFound { url, .. }
+            | ReaderError::Validation { url, .. }
+            | ReaderError::RateLimited { url, .. }
+            | ReaderError::Api { url, .. }
+            | ReaderError::Network { url, .. }
+            | ReaderError::Decode { url, .. } => Some(url),
+            ReaderError::InvalidConfig(_) => None,
+        }
+    }
+}
+
+/// Split an error body into its `detail` message and per-field errors
+///
+/// Reader returns `{"detail": "..."}` for most failures and
+/// `{"field": ["message", ...]}` for validation errors.
+fn parse_error_body(body: &str) -> (Option<String>, BTreeMap<String, Vec<String>>) {
+    let mut detail = None;
+    let mut field_errors = BTreeMap::new();
+
+    let Ok(serde_json::Value::Object(map)) = serde_json::from_str::<serde_json::Value>(body) else {
+        let trimmed = body.trim();
+        if !trimmed.is_empty() {
+            detail = Some(trimmed.to_string());
+        }
+        return (detail, field_errors);
+    };
+
+    for (key, value) in map {
+        if key == "detail" {
+            detail = Some(value_to_message(&value));
+            continue;
+        }
+        let messages = match value {
+            serde_json::Value::Array(items) => items.iter().map(value_to_message).collect(),
+            other => vec![value_to_message(&other)],
+        };
+        field_errors.insert(key, messages);
+    }
+
+    (detail, field_errors)
+}
+
+fn value_to_message(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn describe(
+    detail: &Option<String>,
+    field_errors: Option<&BTreeMap<String, Vec<String>>>,
+) -> String {
+    let mut parts = vec![];
+    if let Some(detail) = detail {
+        parts.push(detail.clone());
+    }
+    for (field, messages) in field_errors.into_iter().flatten() {
+        parts.push(format!("{}: {}", field, messages.join(" ")));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", parts.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    const URL: &str = "https://readwise.io/api/v3/list/";
+
+    #[test]
+    fn unauthorized_keeps_the_detail_message() {
+        let err = ReaderError::from_response(
+            URL,
+            StatusCode::UNAUTHORIZED,
+            r#"{"detail": "Invalid token."}"#.to_string(),
+        );
+        assert!(matches!(err, ReaderError::Unauthorized { status: 401, .. }));
+        assert_eq!(err.status(), Some(401));
+        assert!(!err.is_transient());
+        assert!(err.aborts_batch());
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Authentication failed: HTTP 401 for {}: Invalid token.",
+                URL
+            )
+        );
+    }
+
+    #[test]
+    fn not_found_without_a_body_has_no_detail() {
+        let err = ReaderError::from_response(URL, StatusCode::NOT_FOUND, String::new());
+        assert!(matches!(err, ReaderError::NotFound { detail: None, .. }));
+        assert!(!err.aborts_batch());
+        assert_eq!(err.status(), Some(404));
+        assert_eq!(err.url(), Some(URL));
+        assert_eq!(err.to_string(), format!("Not found: {}", URL));
+    }
+
+    #[test]
+    fn validation_collects_field_errors() {
+        let err = ReaderError::from_response(
+            URL,
+            StatusCode::BAD_REQUEST,
+            r#"{"url": ["Enter a valid URL."], "tags": "Not a list."}"#.to_string(),
+        );
+        let ReaderError::Validation { field_errors, .. } = &err else {
+            panic!("expected a validation error, got {:?}", err);
+        };
+        assert_eq!(field_errors["url"], vec!["Enter a valid URL."]);
+        assert_eq!(field_errors["tags"], vec!["Not a list."]);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Validation failed: HTTP 400 for {}: tags: Not a list.; url: Enter a valid URL.",
+                URL
+            )
+        );
+    }
+
+    #[test]
+    fn rate_limited_reports_the_retry_after() {
+        let err = ReaderError::RateLimited {
+            url: URL.to_string(),
+            retry_after: Some(30),
+        };
+        assert_eq!(err.status(), Some(429));
+        assert!(err.is_transient());
+        assert_eq!(
+            err.to_string(),
+            format!("Rate limited: {} (retry after 30s)", URL)
+        );
+    }
+
+    #[test]
+    fn server_errors_are_transient_and_keep_non_json_bodies() {
+        let body = "<html>Bad Gateway</html>\n".to_string();
+        let err = ReaderError::from_response(URL, StatusCode::BAD_GATEWAY, body.clone());
+        let ReaderError::Api {
+            detail, body: raw, ..
+        } = &err
+        else {
+            panic!("expected an API error, got {:?}", err);
+        };
+        assert_eq!(detail.as_deref(), Some("<html>Bad Gateway</html>"));
+        assert_eq!(raw, &body);
+        assert!(err.is_transient());
+        assert!(err.aborts_batch());
+
+        let err = ReaderError::from_response(URL, StatusCode::CONFLICT, "{}".to_string());
+        assert!(matches!(
+            err,
+            ReaderError::Api {
+                status: 409,
+                detail: None,
+                ..
+            }
+        ));
+        assert!(!err.is_transient());
+    }
+
+    #[test]
+    fn non_string_details_are_rendered_as_json() {
+        let (detail, field_errors) = parse_error_body(r#"{"detail": {"code": 7}}"#);
+        assert_eq!(detail.as_deref(), Some(r#"{"code":7}"#));
+        assert!(field_errors.is_empty());
+    }
+}
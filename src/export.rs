@@ -0,0 +1,386 @@
+//! Markdown export: one file per document, with its metadata in YAML front
+//! matter
+//!
+//! Files are matched to documents by the `id` in their front matter, so a
+//! re-run rewrites the file a document was first exported to, even if it was
+//! renamed or the title has changed since.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use htmd::options::{BulletListMarker, Options};
+use htmd::HtmlToMarkdown;
+use rr::types::Document;
+use serde::Serialize;
+
+/// Longest file name stem derived from a title, in characters
+const MAX_SLUG_CHARS: usize = 80;
+
+/// What an export run did, file by file
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct ExportCounts {
+    pub new: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+/// Writes documents as Markdown files into one directory
+pub struct MarkdownExport {
+    dir: PathBuf,
+    /// Files from earlier exports, by document id
+    by_id: HashMap<String, PathBuf>,
+    converter: HtmlToMarkdown,
+    counts: ExportCounts,
+}
+
+impl MarkdownExport {
+    /// Create `dir` if needed and index the Markdown files already in it
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create export directory {}", dir.display()))?;
+
+        let mut by_id = HashMap::new();
+        let entries = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read export directory {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            // Unreadable files can't be ours; leave them alone
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some(id) = front_matter_id(&content) {
+                by_id.insert(id, path);
+            }
+        }
+
+        let converter = HtmlToMarkdown::builder()
+            .skip_tags(vec!["script", "style", "noscript"])
+            .options(Options {
+                bullet_list_marker: BulletListMarker::Dash,
+                ul_bullet_spacing: 1,
+                ol_number_spacing: 1,
+                ..Default::default()
+            })
+            .build();
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            by_id,
+            converter,
+            counts: ExportCounts::default(),
+        })
+    }
+
+    /// Write `doc` to its file, skipping the write if nothing changed
+    pub fn write(&mut self, doc: &Document) -> Result<()> {
+        let content = self.render(doc)?;
+
+        let path = match self.by_id.get(&doc.id) {
+            Some(path) => path.clone(),
+            None => self.new_path(doc),
+        };
+        match fs::read_to_string(&path) {
+            Ok(existing) if existing == content => self.counts.unchanged += 1,
+            Ok(_) => {
+                self.write_file(&path, &content)?;
+                self.counts.updated += 1;
+            }
+            Err(_) => {
+                self.write_file(&path, &content)?;
+                self.counts.new += 1;
+            }
+        }
+
+        self.by_id.insert(doc.id.clone(), path);
+        Ok(())
+    }
+
+    pub fn counts(&self) -> ExportCounts {
+        self.counts
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> Result<()> {
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// `<slug>.md`, or `<slug>-<id>.md` when that name is already taken by
+    /// another document or a file of the user's
+    fn new_path(&self, doc: &Document) -> PathBuf {
+        let slug = slugify(doc.title.as_deref().unwrap_or_default());
+        if slug.is_empty() {
+            return self.dir.join(format!("{}.md", doc.id));
+        }
+        let path = self.dir.join(format!("{}.md", slug));
+        if path.exists() {
+            self.dir.join(format!("{}-{}.md", slug, doc.id))
+        } else {
+            path
+        }
+    }
+
+    fn render(&self, doc: &Document) -> Result<String> {
+        let mut output = String::from("---\n");
+        let mut field = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                output.push_str(&format!("{}: {}\n", key, value));
+            }
+        };
+        field("id", Some(yaml_string(&doc.id)));
+        field("title", doc.title.as_deref().map(yaml_string));
+        field("author", doc.author.as_deref().map(yaml_string));
+        field("source_url", doc.source_url.as_deref().map(yaml_string));
+        field("category", doc.category.as_deref().map(yaml_string));
+        field("location", doc.location.as_deref().map(yaml_string));
+        let tags: Vec<String> = doc
+            .tags()
+            .iter()
+            .map(|tag| yaml_string(&tag.name))
+            .collect();
+        field("tags", Some(format!("[{}]", tags.join(", "))));
+        field("saved_at", doc.saved_at.as_deref().map(yaml_string));
+        field("word_count", doc.word_count.map(|n| n.to_string()));
+        output.push_str("---\n");
+
+        let body = match doc.html_content.as_deref().filter(|h| !h.trim().is_empty()) {
+            Some(html) => self
+                .converter
+                .convert(html)
+                .with_context(|| format!("Failed to convert document {} to Markdown", doc.id))?,
+            None => [&doc.content, &doc.summary]
+                .into_iter()
+                .flatten()
+                .find(|t| !t.trim().is_empty())
+                .cloned()
+                .unwrap_or_default(),
+        };
+        let body = body.trim();
+        if !body.is_empty() {
+            output.push('\n');
+            output.push_str(body);
+            output.push('\n');
+        }
+        Ok(output)
+    }
+}
+
+/// A YAML double-quoted scalar
+///
+/// JSON string escapes are valid YAML, but JSON leaves DEL and the C1
+/// control characters unescaped, which YAML doesn't allow.
+fn yaml_string(s: &str) -> String {
+    let json = serde_json::Value::from(s).to_string();
+    if !json.chars().any(is_yaml_unprintable) {
+        return json;
+    }
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        if is_yaml_unprintable(c) {
+            escaped.push_str(&format!("\\u{:04x}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Characters JSON passes through but YAML requires escaped
+fn is_yaml_unprintable(c: char) -> bool {
+    c == '\u{7f}' || (('\u{80}'..='\u{9f}').contains(&c) && c != '\u{85}')
+}
+
+/// The `id` from a file's YAML front matter, if it has one
+fn front_matter_id(content: &str) -> Option<String> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    for line in rest.lines() {
+        if line.trim_end() == "---" {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("id:") {
+            let value = value.trim();
+            let id = if value.starts_with('"') {
+                serde_json::from_str(value).ok()?
+            } else {
+                value.trim_matches('\'').to_string()
+            };
+            return Some(id).filter(|id| !id.is_empty());
+        }
+    }
+    None
+}
+
+/// Lowercase words of `title` joined by hyphens, safe as a file name
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        // Combining accents stay with their letter, as in decomposed `é`
+        if c.is_alphanumeric() || (is_combining_mark(c) && !slug.is_empty() && !slug.ends_with('-'))
+        {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.chars().count() >= MAX_SLUG_CHARS {
+            break;
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Whether `c` is in one of the blocks of combining diacritical marks
+fn is_combining_mark(c: char) -> bool {
+    matches!(c,
+        '\u{300}'..='\u{36f}'
+        | '\u{1ab0}'..='\u{1aff}'
+        | '\u{1dc0}'..='\u{1dff}'
+        | '\u{20d0}'..='\u{20ff}'
+        | '\u{fe20}'..='\u{fe2f}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(id: &str, title: &str) -> Document {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "source_url": "https://example.com",
+            "title": title,
+            "tags": {"rust": {"name": "Rust \"lang\""}},
+            "html_content": "<h2>Hi</h2><ul><li>one</li></ul><script>x()</script>",
+        }))
+        .unwrap()
+    }
+
+    fn md_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn slugify_titles() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(
+            slugify("  --Rust's  \"async\"\n story-- "),
+            "rust-s-async-story"
+        );
+        assert_eq!(slugify("Café Ünïcode 日本語"), "café-ünïcode-日本語");
+        assert_eq!(slugify("İstanbul"), "i\u{307}stanbul");
+        assert_eq!(slugify("Cafe\u{301} au lait"), "cafe\u{301}-au-lait");
+        assert_eq!(slugify("\u{301}accent first"), "accent-first");
+        assert_eq!(slugify("../../etc/passwd"), "etc-passwd");
+        assert_eq!(slugify("?!"), "");
+        assert_eq!(slugify(&"a".repeat(200)).chars().count(), MAX_SLUG_CHARS);
+        assert!(!slugify(&"ab ".repeat(100)).ends_with('-'));
+    }
+
+    #[test]
+    fn yaml_string_escapes() {
+        assert_eq!(yaml_string("plain"), r#""plain""#);
+        assert_eq!(yaml_string(r#"say "hi" \ bye"#), r#""say \"hi\" \\ bye""#);
+        assert_eq!(yaml_string("two\nlines\ttab"), r#""two\nlines\ttab""#);
+        assert_eq!(yaml_string("émoji 🎉: yes # no"), "\"émoji 🎉: yes # no\"");
+        assert_eq!(
+            yaml_string("del\u{7f} c1\u{9b} nel\u{85}"),
+            "\"del\\u007f c1\\u009b nel\u{85}\""
+        );
+    }
+
+    #[test]
+    fn front_matter_id_reads_quoted_and_plain_ids() {
+        assert_eq!(
+            front_matter_id("---\nid: \"a\\\"b\"\n---\n").as_deref(),
+            Some("a\"b")
+        );
+        assert_eq!(
+            front_matter_id("---\r\ntitle: x\r\nid: 'abc'\r\n---\r\n").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            front_matter_id("---\nid: plain\n").as_deref(),
+            Some("plain")
+        );
+        assert_eq!(front_matter_id("---\ntitle: x\n---\nid: body\n"), None);
+        assert_eq!(front_matter_id("id: no front matter\n"), None);
+        assert_eq!(front_matter_id("---\nid: \"\"\n---\n"), None);
+    }
+
+    #[test]
+    fn rendered_front_matter_round_trips_the_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut export = MarkdownExport::open(dir.path()).unwrap();
+        let doc = document("0\"odd: id", "Quotes \"here\"\nand a newline");
+        export.write(&doc).unwrap();
+
+        let path = dir.path().join("quotes-here-and-a-newline.md");
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(
+            content.starts_with(
+                "---\nid: \"0\\\"odd: id\"\ntitle: \"Quotes \\\"here\\\"\\nand a newline\"\n"
+            ),
+            "{}",
+            content
+        );
+        assert!(
+            content.contains("tags: [\"Rust \\\"lang\\\"\"]\n"),
+            "{}",
+            content
+        );
+        assert!(content.ends_with("---\n\n## Hi\n\n- one\n"), "{}", content);
+        assert_eq!(front_matter_id(&content).as_deref(), Some(doc.id.as_str()));
+    }
+
+    #[test]
+    fn same_titles_get_distinct_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("mine.md"), "my own notes").unwrap();
+        let mut export = MarkdownExport::open(dir.path()).unwrap();
+        export.write(&document("d1", "Same")).unwrap();
+        export.write(&document("d2", "Same")).unwrap();
+        export.write(&document("d3", "Mine")).unwrap();
+        export.write(&document("d4", "!!!")).unwrap();
+
+        assert_eq!(
+            md_files(dir.path()),
+            ["d4.md", "mine-d3.md", "mine.md", "same-d2.md", "same.md"]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("mine.md")).unwrap(),
+            "my own notes"
+        );
+        assert_eq!(export.counts().new, 4);
+    }
+
+    #[test]
+    fn rerun_updates_files_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut export = MarkdownExport::open(dir.path()).unwrap();
+        export.write(&document("d1", "First title")).unwrap();
+        export.write(&document("d2", "Other")).unwrap();
+        fs::rename(
+            dir.path().join("first-title.md"),
+            dir.path().join("renamed.md"),
+        )
+        .unwrap();
+
+        let mut export = MarkdownExport::open(dir.path()).unwrap();
+        export.write(&document("d1", "New title")).unwrap();
+        export.write(&document("d2", "Other")).unwrap();
+        let counts = export.counts();
+        assert_eq!((counts.new, counts.updated, counts.unchanged), (0, 1, 1));
+        assert_eq!(md_files(dir.path()), ["other.md", "renamed.md"]);
+        assert!(fs::read_to_string(dir.path().join("renamed.md"))
+            .unwrap()
+            .contains("title: \"New title\""));
+    }
+}
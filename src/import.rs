@@ -0,0 +1,654 @@
+//! Parsers for the bookmark exports of other read-it-later services, and the
+//! checkpoint that lets `rr import` resume
+//!
+//! Reader's save endpoint has no field for when a bookmark was first saved,
+//! so that time goes into the document's note instead.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::ImportFormat;
+use crate::report::SaveFailure;
+use crate::util::{decode_entities, format_timestamp, input_sha256};
+use rr::atomic_file::write_atomic;
+use rr::types::CreateDocumentRequest;
+
+/// A bookmark as found in an export, before mapping to Reader's fields
+#[derive(Debug, Default)]
+struct Bookmark {
+    url: String,
+    title: Option<String>,
+    author: Option<String>,
+    summary: Option<String>,
+    tags: Vec<String>,
+    /// Reader location; taken as-is from generic CSV files
+    location: Option<String>,
+    category: Option<String>,
+    published_date: Option<String>,
+    /// Read or archived in the other service
+    archived: Option<bool>,
+    /// When it was saved there, as Unix seconds or a date string
+    saved_at: Option<String>,
+    /// Description or highlighted text saved with the bookmark
+    note: Option<String>,
+}
+
+impl Bookmark {
+    fn into_request(self, service: Option<&str>) -> CreateDocumentRequest {
+        let saved = self.saved_at.filter(|s| !s.is_empty()).map(|saved_at| {
+            let date = match saved_at.parse::<u64>() {
+                Ok(secs) => format_timestamp(secs),
+                Err(_) => saved_at,
+            };
+            match service {
+                Some(service) => format!("Saved to {} on {}", service, date),
+                None => format!("Originally saved on {}", date),
+            }
+        });
+        let notes: Vec<String> = [self.note, saved]
+            .into_iter()
+            .flatten()
+            .filter(|n| !n.trim().is_empty())
+            .collect();
+        let location = self.location.or(match self.archived {
+            Some(true) => Some("archive".to_string()),
+            Some(false) => Some("later".to_string()),
+            None => None,
+        });
+
+        CreateDocumentRequest {
+            url: self.url,
+            html: None,
+            should_clean_html: None,
+            title: self.title.filter(|t| !t.is_empty()),
+            author: self.author.filter(|a| !a.is_empty()),
+            summary: self.summary.filter(|s| !s.is_empty()),
+            published_date: self.published_date.filter(|d| !d.is_empty()),
+            image_url: None,
+            location,
+            category: self.category.filter(|c| !c.is_empty()),
+            saved_using: service.map(str::to_string),
+            tags: Some(self.tags).filter(|tags| !tags.is_empty()),
+            notes: Some(notes.join("\n\n")).filter(|n| !n.is_empty()),
+        }
+    }
+}
+
+/// Parse an export file into save requests, in file order
+pub fn parse(format: ImportFormat, input: &str) -> Result<Vec<CreateDocumentRequest>> {
+    let (bookmarks, service) = match format {
+        ImportFormat::Pocket if input.trim_start().starts_with('<') => {
+            (parse_bookmark_html(input, true), Some("Pocket"))
+        }
+        ImportFormat::Pocket => (parse_pocket_csv(input)?, Some("Pocket")),
+        ImportFormat::Instapaper => (parse_instapaper(input)?, Some("Instapaper")),
+        ImportFormat::Pinboard => (parse_pinboard(input)?, Some("Pinboard")),
+        ImportFormat::NetscapeHtml => (parse_bookmark_html(input, false), None),
+        ImportFormat::Csv => (parse_csv(input)?, None),
+    };
+    Ok(bookmarks
+        .into_iter()
+        .filter(|b| !b.url.trim().is_empty())
+        .map(|b| b.into_request(service))
+        .collect())
+}
+
+/// Netscape bookmark files, including Pocket's HTML export
+///
+/// With `pocket_sections`, Pocket's "Unread" and "Read Archive" headings set
+/// the read state of the links after them, up to the end of the enclosing
+/// `<DL>`. Other files' folder names say nothing about read state.
+fn parse_bookmark_html(input: &str, pocket_sections: bool) -> Vec<Bookmark> {
+    let mut bookmarks = vec![];
+    let mut open_link: Option<Bookmark> = None;
+    let mut in_heading = false;
+    let mut archived = None;
+    // Read state outside each open `<DL>`, restored when it closes
+    let mut outer_archived = vec![];
+    let mut in_description = false;
+    let mut text = String::new();
+
+    let mut rest = input;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        match name.to_ascii_lowercase().as_str() {
+            "a" => {
+                let attrs = parse_attributes(attrs);
+                let Some(url) = attrs.get("href") else {
+                    continue;
+                };
+                text.clear();
+                in_description = false;
+                open_link = Some(Bookmark {
+                    url: url.clone(),
+                    tags: split_tags(attrs.get("tags").map_or("", |t| t.as_str()), &[',']),
+                    archived: match attrs.get("toread").map(String::as_str) {
+                        Some("1") => Some(false),
+                        _ => archived,
+                    },
+                    saved_at: attrs
+                        .get("time_added")
+                        .or_else(|| attrs.get("add_date"))
+                        .cloned(),
+                    ..Default::default()
+                });
+            }
+            "/a" => {
+                if let Some(mut bookmark) = open_link.take() {
+                    bookmark.title = Some(decode_entities(text.trim()));
+                    bookmarks.push(bookmark);
+                }
+                text.clear();
+            }
+            "h1" | "h2" | "h3" => {
+                in_description = false;
+                text.clear();
+                in_heading = true;
+            }
+            "/h1" | "/h2" | "/h3" => {
+                if std::mem::take(&mut in_heading) && pocket_sections {
+                    let name = decode_entities(text.trim()).to_lowercase();
+                    if name == "unread" {
+                        archived = Some(false);
+                    } else if name.contains("archive") {
+                        archived = Some(true);
+                    }
+                }
+                text.clear();
+            }
+            "dd" => {
+                text.clear();
+                in_description = open_link.is_none() && !bookmarks.is_empty();
+            }
+            "dl" => {
+                finish_description(&mut bookmarks, &mut in_description, &text);
+                text.clear();
+                outer_archived.push(archived);
+            }
+            "/dl" => {
+                finish_description(&mut bookmarks, &mut in_description, &text);
+                text.clear();
+                if let Some(outer) = outer_archived.pop() {
+                    archived = outer;
+                }
+            }
+            "dt" | "li" | "/ul" => {
+                finish_description(&mut bookmarks, &mut in_description, &text);
+                text.clear();
+            }
+            _ => {}
+        }
+    }
+    finish_description(&mut bookmarks, &mut in_description, &text);
+    bookmarks
+}
+
+/// Attach the text after a `<DD>` to the bookmark it follows
+fn finish_description(bookmarks: &mut [Bookmark], in_description: &mut bool, text: &str) {
+    if std::mem::take(in_description) {
+        if let Some(last) = bookmarks.last_mut() {
+            let note = decode_entities(text.trim());
+            last.note = Some(note).filter(|n| !n.is_empty());
+        }
+    }
+}
+
+/// `name="value"` pairs of a tag, with lowercase names
+fn parse_attributes(mut attrs: &str) -> HashMap<String, String> {
+    let mut result = HashMap::new();
+    loop {
+        attrs = attrs.trim_start();
+        let name_end = attrs
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(attrs.len());
+        if name_end == 0 {
+            break;
+        }
+        let name = attrs[..name_end].to_ascii_lowercase();
+        attrs = attrs[name_end..].trim_start();
+
+        let Some(value_part) = attrs.strip_prefix('=') else {
+            result.insert(name, String::new());
+            continue;
+        };
+        let value_part = value_part.trim_start();
+        let (value, remainder) = match value_part.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let inner = &value_part[1..];
+                let end = inner.find(quote).unwrap_or(inner.len());
+                (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+            }
+            _ => {
+                let end = value_part
+                    .find(char::is_whitespace)
+                    .unwrap_or(value_part.len());
+                (&value_part[..end], &value_part[end..])
+            }
+        };
+        result.insert(name, decode_entities(value));
+        attrs = remainder;
+    }
+    result
+}
+
+fn split_tags(tags: &str, separators: &[char]) -> Vec<String> {
+    tags.split(separators)
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Rows of a CSV file with a header, as maps from lowercase column name
+fn csv_rows(input: &str) -> Result<Vec<HashMap<String, String>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(input.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read the CSV header")?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    reader
+        .records()
+        .enumerate()
+        .map(|(idx, record)| {
+            // Line numbers count the header
+            let record = record.with_context(|| format!("Invalid CSV row {}", idx + 2))?;
+            Ok(headers
+                .iter()
+                .cloned()
+                .zip(record.iter().map(|v| v.trim().to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+/// The first of `names` that `row` has a non-empty value for
+fn column(row: &HashMap<String, String>, names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| row.get(*name))
+        .find(|v| !v.is_empty())
+        .cloned()
+}
+
+fn require_column(rows: &[HashMap<String, String>], name: &str, format: &str) -> Result<()> {
+    match rows.first() {
+        Some(row) if !row.contains_key(name) => {
+            bail!("Not a {} export: no `{}` column", format, name)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Pocket's CSV export: `title,url,time_added,tags,status`, tags split by `|`
+fn parse_pocket_csv(input: &str) -> Result<Vec<Bookmark>> {
+    let rows = csv_rows(input)?;
+    require_column(&rows, "url", "Pocket CSV")?;
+    Ok(rows
+        .iter()
+        .map(|row| Bookmark {
+            url: column(row, &["url"]).unwrap_or_default(),
+            title: column(row, &["title"]),
+            tags: split_tags(&column(row, &["tags"]).unwrap_or_default(), &['|']),
+            archived: column(row, &["status"]).map(|s| s == "archive"),
+            saved_at: column(row, &["time_added"]),
+            ..Default::default()
+        })
+        .collect())
+}
+
+/// Instapaper's CSV export: `URL,Title,Selection,Folder,Timestamp,Tags`
+///
+/// Folders other than Unread and Archive (including Starred) become tags.
+fn parse_instapaper(input: &str) -> Result<Vec<Bookmark>> {
+    let rows = csv_rows(input)?;
+    require_column(&rows, "url", "Instapaper")?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            // Tags are a JSON array in newer exports
+            let tags = column(row, &["tags"]).unwrap_or_default();
+            let mut tags = serde_json::from_str::<Vec<String>>(&tags)
+                .unwrap_or_else(|_| split_tags(&tags, &[',']));
+            let folder = column(row, &["folder"]).unwrap_or_default();
+            let archived = match folder.to_lowercase().as_str() {
+                "archive" => true,
+                "unread" | "" => false,
+                _ => {
+                    tags.push(folder.to_lowercase());
+                    false
+                }
+            };
+            Bookmark {
+                url: column(row, &["url"]).unwrap_or_default(),
+                title: column(row, &["title"]),
+                tags,
+                archived: Some(archived),
+                saved_at: column(row, &["timestamp"]),
+                note: column(row, &["selection"]),
+                ..Default::default()
+            }
+        })
+        .collect())
+}
+
+/// One bookmark of Pinboard's JSON export
+#[derive(Deserialize)]
+struct PinboardPost {
+    href: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    extended: Option<String>,
+    #[serde(default)]
+    time: Option<String>,
+    #[serde(default)]
+    toread: Option<String>,
+    #[serde(default)]
+    tags: Option<String>,
+}
+
+/// Pinboard's JSON export; bookmarks not marked "to read" are archived
+fn parse_pinboard(input: &str) -> Result<Vec<Bookmark>> {
+    let posts: Vec<PinboardPost> =
+        serde_json::from_str(input).context("Not a Pinboard JSON export")?;
+    Ok(posts
+        .into_iter()
+        .map(|post| Bookmark {
+            url: post.href,
+            title: post.description,
+            tags: split_tags(&post.tags.unwrap_or_default(), &[' ']),
+            archived: Some(post.toread.as_deref() != Some("yes")),
+            saved_at: post.time,
+            note: post.extended,
+            ..Default::default()
+        })
+        .collect())
+}
+
+/// CSV with columns named after Reader's fields
+fn parse_csv(input: &str) -> Result<Vec<Bookmark>> {
+    let rows = csv_rows(input)?;
+    require_column(&rows, "url", "CSV")?;
+    Ok(rows
+        .iter()
+        .map(|row| Bookmark {
+            url: column(row, &["url"]).unwrap_or_default(),
+            title: column(row, &["title"]),
+            author: column(row, &["author"]),
+            summary: column(row, &["summary"]),
+            tags: split_tags(&column(row, &["tags"]).unwrap_or_default(), &[',', '|']),
+            location: column(row, &["location"]),
+            category: column(row, &["category"]),
+            published_date: column(row, &["published_date"]),
+            saved_at: column(row, &["saved_at"]),
+            note: column(row, &["notes"]),
+            ..Default::default()
+        })
+        .collect())
+}
+
+/// How far an import of one file has got
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// SHA-256 of the export file; a different file starts over
+    pub input_sha256: String,
+    /// Index of the next bookmark to save
+    pub next: usize,
+    pub created: usize,
+    pub failed: Vec<SaveFailure>,
+}
+
+impl Checkpoint {
+    /// The checkpoint at `path` if it belongs to `input`, else a fresh one
+    pub fn load(path: &Path, input: &str) -> Result<Self> {
+        let input_sha256 = input_sha256(input);
+        let checkpoint = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str::<Checkpoint>(&content).with_context(|| {
+                format!(
+                    "Invalid import checkpoint {}; delete it to start over",
+                    path.display()
+                )
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Checkpoint::default(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read checkpoint {}", path.display()))
+            }
+        };
+        if checkpoint.input_sha256 == input_sha256 {
+            Ok(checkpoint)
+        } else {
+            Ok(Checkpoint {
+                input_sha256,
+                ..Default::default()
+            })
+        }
+    }
+
+    /// Replace the checkpoint file atomically
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+        }
+        write_atomic(path, &serde_json::to_vec(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POCKET_HTML: &str = r#"<!DOCTYPE html>
+<html><head><title>Pocket Export</title></head><body>
+<h1>Unread</h1>
+<ul>
+<li><a href="https://example.com/unread" time_added="1700000000" tags="rust,cli">Unread &amp; new</a></li>
+</ul>
+<h1>Read Archive</h1>
+<ul>
+<li><a href="https://example.com/read" time_added="1700000001" tags="">Read</a></li>
+</ul>
+</body></html>"#;
+
+    const NETSCAPE_HTML: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3>Web Archive tools</H3>
+    <DL><p>
+        <DT><A HREF="https://web.archive.org/" ADD_DATE="1700000000" TAGS="tools">Wayback</A>
+    </DL><p>
+    <DT><H3>Recipes</H3>
+    <DL><p>
+        <DT><A HREF="https://example.org/soup" TOREAD="1">Soup</A>
+        <DD>Good soup, &lt;really&gt;
+        <DT><A HREF="https://example.org/bread">Bread</A>
+    </DL><p>
+</DL><p>"#;
+
+    #[test]
+    fn pocket_html_sections_set_read_state() {
+        let bookmarks = parse_bookmark_html(POCKET_HTML, true);
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].url, "https://example.com/unread");
+        assert_eq!(bookmarks[0].title.as_deref(), Some("Unread & new"));
+        assert_eq!(bookmarks[0].tags, ["rust", "cli"]);
+        assert_eq!(bookmarks[0].archived, Some(false));
+        assert_eq!(bookmarks[0].saved_at.as_deref(), Some("1700000000"));
+        assert_eq!(bookmarks[1].archived, Some(true));
+    }
+
+    #[test]
+    fn pocket_html_maps_to_save_requests() {
+        let requests = parse(ImportFormat::Pocket, POCKET_HTML).unwrap();
+        assert_eq!(requests[0].location.as_deref(), Some("later"));
+        assert_eq!(requests[1].location.as_deref(), Some("archive"));
+        assert_eq!(requests[0].saved_using.as_deref(), Some("Pocket"));
+        assert_eq!(
+            requests[0].notes.as_deref(),
+            Some("Saved to Pocket on 2023-11-14 22:13:20 UTC")
+        );
+        assert_eq!(requests[1].tags, None);
+    }
+
+    #[test]
+    fn netscape_folder_names_do_not_set_read_state() {
+        let bookmarks = parse_bookmark_html(NETSCAPE_HTML, false);
+        let urls: Vec<_> = bookmarks.iter().map(|b| b.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://web.archive.org/",
+                "https://example.org/soup",
+                "https://example.org/bread"
+            ]
+        );
+        assert_eq!(bookmarks[0].archived, None);
+        assert_eq!(bookmarks[0].tags, ["tools"]);
+        assert_eq!(bookmarks[0].saved_at.as_deref(), Some("1700000000"));
+        assert_eq!(bookmarks[1].archived, Some(false));
+        assert_eq!(bookmarks[2].archived, None);
+
+        let requests = parse(ImportFormat::NetscapeHtml, NETSCAPE_HTML).unwrap();
+        assert_eq!(requests[0].location, None);
+        assert_eq!(requests[0].saved_using, None);
+    }
+
+    #[test]
+    fn pocket_headings_only_apply_inside_their_list() {
+        let input = r#"<DL><p>
+<DT><H3>Read Archive</H3>
+<DL><p><DT><A HREF="https://example.com/inner">Inner</A></DL><p>
+</DL><p>
+<DL><p><DT><A HREF="https://example.com/outer">Outer</A></DL><p>"#;
+        let bookmarks = parse_bookmark_html(input, true);
+        assert_eq!(bookmarks[0].archived, Some(true));
+        assert_eq!(bookmarks[1].archived, None);
+    }
+
+    #[test]
+    fn dd_notes_attach_to_the_preceding_bookmark() {
+        let bookmarks = parse_bookmark_html(NETSCAPE_HTML, false);
+        assert_eq!(bookmarks[0].note, None);
+        assert_eq!(bookmarks[1].note.as_deref(), Some("Good soup, <really>"));
+        assert_eq!(bookmarks[2].note, None);
+
+        let leading =
+            parse_bookmark_html("<DL><DD>Orphan<DT><A HREF=\"https://a\">A</A></DL>", false);
+        assert_eq!(leading[0].note, None);
+    }
+
+    #[test]
+    fn parse_attributes_handles_quoting_and_case() {
+        let attrs = parse_attributes(
+            r#"HREF="https://example.com/?a=1&amp;b=2" tags='x, y'  toread=1 private"#,
+        );
+        assert_eq!(attrs["href"], "https://example.com/?a=1&b=2");
+        assert_eq!(attrs["tags"], "x, y");
+        assert_eq!(attrs["toread"], "1");
+        assert_eq!(attrs["private"], "");
+        assert_eq!(attrs.len(), 4);
+    }
+
+    #[test]
+    fn parse_attributes_tolerates_unterminated_quotes() {
+        let attrs = parse_attributes(r#"href = "https://example.com"#);
+        assert_eq!(attrs["href"], "https://example.com");
+        assert!(parse_attributes("   ").is_empty());
+    }
+
+    #[test]
+    fn pocket_csv_columns() {
+        let input = "title,url,time_added,tags,status\n\
+                     One,https://example.com/1,1700000000,a|b,unread\n\
+                     Two,https://example.com/2,1700000001,,archive\n";
+        let bookmarks = parse_pocket_csv(input).unwrap();
+        assert_eq!(bookmarks[0].title.as_deref(), Some("One"));
+        assert_eq!(bookmarks[0].tags, ["a", "b"]);
+        assert_eq!(bookmarks[0].archived, Some(false));
+        assert_eq!(bookmarks[1].archived, Some(true));
+        assert_eq!(bookmarks[1].saved_at.as_deref(), Some("1700000001"));
+    }
+
+    #[test]
+    fn instapaper_folders_become_read_state_or_tags() {
+        let input = "URL,Title,Selection,Folder,Timestamp,Tags\n\
+                     https://example.com/1,One,Quoted,Unread,1700000000,\"[\"\"x\"\"]\"\n\
+                     https://example.com/2,Two,,Archive,1700000001,\n\
+                     https://example.com/3,Three,,Starred,1700000002,\"y, z\"\n";
+        let bookmarks = parse_instapaper(input).unwrap();
+        assert_eq!(bookmarks[0].archived, Some(false));
+        assert_eq!(bookmarks[0].tags, ["x"]);
+        assert_eq!(bookmarks[0].note.as_deref(), Some("Quoted"));
+        assert_eq!(bookmarks[1].archived, Some(true));
+        assert_eq!(bookmarks[2].archived, Some(false));
+        assert_eq!(bookmarks[2].tags, ["y", "z", "starred"]);
+    }
+
+    #[test]
+    fn pinboard_toread_is_unread() {
+        let input = r#"[
+            {"href": "https://example.com/1", "description": "One", "extended": "Note",
+             "time": "2020-01-01T00:00:00Z", "toread": "yes", "tags": "a b"},
+            {"href": "https://example.com/2", "toread": "no"}
+        ]"#;
+        let bookmarks = parse_pinboard(input).unwrap();
+        assert_eq!(bookmarks[0].archived, Some(false));
+        assert_eq!(bookmarks[0].tags, ["a", "b"]);
+        assert_eq!(bookmarks[0].note.as_deref(), Some("Note"));
+        assert_eq!(bookmarks[1].archived, Some(true));
+    }
+
+    #[test]
+    fn generic_csv_columns_map_to_reader_fields() {
+        let input = "URL,Title,Author,Summary,Tags,Location,Category,Published_Date,Saved_At,Notes\n\
+                     https://example.com/1,One,Ann,Sum,a|b,shortlist,pdf,2020-01-01,2021-02-03,Hi\n";
+        let requests = parse(ImportFormat::Csv, input).unwrap();
+        let request = &requests[0];
+        assert_eq!(request.url, "https://example.com/1");
+        assert_eq!(request.title.as_deref(), Some("One"));
+        assert_eq!(request.author.as_deref(), Some("Ann"));
+        assert_eq!(request.summary.as_deref(), Some("Sum"));
+        assert_eq!(
+            request.tags.as_deref(),
+            Some(&["a".to_string(), "b".to_string()][..])
+        );
+        assert_eq!(request.location.as_deref(), Some("shortlist"));
+        assert_eq!(request.category.as_deref(), Some("pdf"));
+        assert_eq!(request.published_date.as_deref(), Some("2020-01-01"));
+        assert_eq!(
+            request.notes.as_deref(),
+            Some("Hi\n\nOriginally saved on 2021-02-03")
+        );
+        assert_eq!(request.saved_using, None);
+    }
+
+    #[test]
+    fn csv_without_a_url_column_is_rejected() {
+        let error = parse(ImportFormat::Csv, "title\nOne\n").unwrap_err();
+        assert!(error.to_string().contains("no `url` column"));
+    }
+
+    #[test]
+    fn rows_without_a_url_are_skipped() {
+        let requests = parse(ImportFormat::Csv, "url,title\n,Empty\nhttps://a,A\n").unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://a");
+    }
+}
@@ -0,0 +1,51 @@
+//! Client library for the [Readwise Reader API](https://readwise.io/reader_api).
+//!
+//! This crate powers the `rr` command-line tool, but the pieces are usable on
+//! their own:
+//!
+//! - [`ReaderClient`] talks to the API (auth, save, list, update, delete, tags);
+//!   [`ReaderClient::documents_stream`] and [`ReaderClient::tags_stream`]
+//!   follow pagination for you
+//! - [`types`] holds the request/response types, including the
+//!   [`ListDocumentsParams`] query builder
+//! - [`Cache`] is the on-disk response cache used by the CLI
+//! - [`WriteQueue`] journals writes made offline for later replay
+//! - [`BackupWriter`] and [`BackupReader`] write and read full library backups
+//! - [`ReaderError`] distinguishes auth, not-found, validation, rate-limit,
+//!   network and decode failures
+//!
+//! ```no_run
+//! use rr::{ListDocumentsParams, ReaderClient, DEFAULT_BASE_URL};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let mut client = ReaderClient::new("my-token", DEFAULT_BASE_URL, false)?;
+//! let params = ListDocumentsParams {
+//!     location: Some("later".to_string()),
+//!     ..Default::default()
+//! };
+//! let page = client.list_documents(&params).await?;
+//! for doc in &page.results {
+//!     println!("{}", doc.title.as_deref().unwrap_or("(no title)"));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod atomic_file;
+pub mod backup;
+pub mod cache;
+pub mod client;
+pub mod error;
+pub mod queue;
+pub mod rate_limit;
+pub mod retry;
+pub mod types;
+
+pub use backup::{BackupReader, BackupRecord, BackupWriter};
+pub use cache::{Cache, CacheEntry, CacheFile, CacheStats, ImportCounts, MergeCounts, SyncState};
+pub use client::{DebugCache, DebugEntry, ReaderClient, WaitNotice, DEFAULT_BASE_URL};
+pub use error::ReaderError;
+pub use queue::{QueuedOp, QueuedWrite, WriteQueue};
+pub use rate_limit::{RateLimiter, RateLimits};
+pub use retry::RetryPolicy;
+pub use types::*;
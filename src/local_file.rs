@@ -0,0 +1,274 @@
+//! Documents read from local HTML, Markdown or plain text files for
+//! `rr create --file`
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+use crate::util::{decode_entities, input_sha256, text_to_html};
+
+/// Host of the URLs made up for files saved without `--url`; `.invalid`
+/// never resolves, so Reader can't fetch anything else in their place
+const FILE_URL_BASE: &str = "https://files.rr.invalid";
+
+/// A local file, ready to save
+pub struct LocalDocument {
+    pub html: String,
+    /// The front matter `title`, first heading or `<title>`, else the file
+    /// name without extension
+    pub title: String,
+    /// Derived from the file's canonical path, so saving the same file again,
+    /// even after editing it, finds the existing document instead of adding
+    /// a copy
+    pub url: String,
+    /// Only HTML written by someone else needs Reader's cleanup; HTML
+    /// generated here is already clean
+    pub should_clean_html: bool,
+}
+
+enum Kind {
+    Html,
+    Markdown,
+    Text,
+}
+
+/// Read `path`, choosing how to convert it from its extension
+pub fn load(path: &Path) -> Result<LocalDocument> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let kind = match extension.as_str() {
+        "html" | "htm" | "xhtml" => Kind::Html,
+        "md" | "markdown" | "mdown" | "mkd" => Kind::Markdown,
+        _ => Kind::Text,
+    };
+
+    let (html, title) = match kind {
+        Kind::Html => {
+            let title = html_title(&content);
+            (content.clone(), title)
+        }
+        Kind::Markdown => markdown_to_html(&content),
+        Kind::Text => (text_to_html(&content), None),
+    };
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let title = title.filter(|t| !t.is_empty()).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+
+    let canonical =
+        fs::canonicalize(path).with_context(|| format!("Failed to resolve {}", path.display()))?;
+
+    Ok(LocalDocument {
+        url: file_url(&canonical, &file_name),
+        should_clean_html: matches!(kind, Kind::Html),
+        html,
+        title,
+    })
+}
+
+/// Render Markdown (with tables, footnotes, strikethrough and task lists),
+/// returning its title too: the `title` of its YAML front matter, else the
+/// text of its first heading
+///
+/// The front matter itself isn't rendered.
+fn markdown_to_html(markdown: &str) -> (String, Option<String>) {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+    let events: Vec<Event> = Parser::new_ext(markdown, options).collect();
+
+    let mut front_matter_title = None;
+    let mut heading: Option<String> = None;
+    let mut in_metadata = false;
+    let mut in_heading = false;
+    for event in &events {
+        match event {
+            Event::Start(Tag::MetadataBlock(_)) => in_metadata = true,
+            Event::End(TagEnd::MetadataBlock(_)) => in_metadata = false,
+            Event::Text(text) if in_metadata => {
+                front_matter_title =
+                    front_matter_title.or_else(|| front_matter_field(text, "title"));
+            }
+            Event::Start(Tag::Heading { .. }) if heading.is_none() => {
+                in_heading = true;
+                heading = Some(String::new());
+            }
+            Event::End(TagEnd::Heading(_)) => in_heading = false,
+            Event::Text(text) | Event::Code(text) if in_heading => {
+                if let Some(heading) = &mut heading {
+                    heading.push_str(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    let title = front_matter_title.or(heading.map(|h| h.trim().to_string()));
+    (html, title)
+}
+
+/// Value of a top-level `key: value` line of YAML front matter, unquoted
+fn front_matter_field(yaml: &str, key: &str) -> Option<String> {
+    yaml.lines().find_map(|line| {
+        let value = line
+            .strip_prefix(key)?
+            .trim_start()
+            .strip_prefix(':')?
+            .trim();
+        let value = ['"', '\'']
+            .into_iter()
+            .find_map(|quote| value.strip_prefix(quote)?.strip_suffix(quote))
+            .unwrap_or(value);
+        Some(value.to_string()).filter(|v| !v.is_empty())
+    })
+}
+
+/// Text of the `<title>` element, else of the first `<h1>`
+fn html_title(html: &str) -> Option<String> {
+    ["title", "h1"]
+        .into_iter()
+        .find_map(|tag| element_text(html, tag))
+}
+
+/// Text content of the first `<tag>` element, with markup removed
+fn element_text(html: &str, tag: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{}", tag);
+    let mut search_from = 0;
+    let start = loop {
+        let at = search_from + lower[search_from..].find(&open)?;
+        let after = lower[at + open.len()..].chars().next()?;
+        // `<title>` or `<h1 class=...>`, not `<titles>` or `<h10>`
+        if after == '>' || after.is_whitespace() {
+            break at + lower[at..].find('>')? + 1;
+        }
+        search_from = at + open.len();
+    };
+    let end = start + lower[start..].find(&format!("</{}", tag))?;
+
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html[start..end].chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(text).filter(|t| !t.is_empty())
+}
+
+/// `https://files.rr.invalid/<path hash>/<file name>`
+fn file_url(canonical_path: &Path, file_name: &str) -> String {
+    let hash = input_sha256(&canonical_path.to_string_lossy());
+    let name: String = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{}/{}/{}", FILE_URL_BASE, &hash[..16], name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_title_prefers_the_title_element() {
+        let html =
+            "<html><head><TITLE>Page &amp; co</TITLE></head><body><h1>Heading</h1></body></html>";
+        assert_eq!(html_title(html).as_deref(), Some("Page & co"));
+        let html = "<body><h1 class=\"big\">The <em>real</em>\n  heading</h1></body>";
+        assert_eq!(html_title(html).as_deref(), Some("The real heading"));
+        assert_eq!(html_title("<titles>no</titles><h10>no</h10>"), None);
+        assert_eq!(html_title("<title> </title>"), None);
+    }
+
+    #[test]
+    fn markdown_title_comes_from_front_matter_or_the_first_heading() {
+        let (html, title) =
+            markdown_to_html("---\ntitle: \"From front matter\"\ntags: [a]\n---\n\n# Heading\n");
+        assert_eq!(title.as_deref(), Some("From front matter"));
+        assert!(!html.contains("tags"), "{}", html);
+        assert!(html.contains("<h1>Heading</h1>"), "{}", html);
+
+        let (_, title) =
+            markdown_to_html("---\nauthor: me\n---\n\nIntro\n\n## The `first` one\n\n# Second\n");
+        assert_eq!(title.as_deref(), Some("The first one"));
+
+        let (_, title) = markdown_to_html("No headings at all\n");
+        assert_eq!(title, None);
+    }
+
+    #[test]
+    fn markdown_extensions_are_rendered() {
+        let (html, _) = markdown_to_html(
+            "| a | b |\n|---|---|\n| 1 | 2 |\n\n~~old~~\n\n- [x] done\n\nNote[^1]\n\n[^1]: Footnote\n",
+        );
+        assert!(html.contains("<table>"), "{}", html);
+        assert!(html.contains("<del>old</del>"), "{}", html);
+        assert!(html.contains("type=\"checkbox\""), "{}", html);
+        assert!(html.contains("footnote-definition"), "{}", html);
+    }
+
+    #[test]
+    fn load_falls_back_to_the_file_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("my notes.txt");
+        fs::write(&path, "Line one\nline <two>\n\nNext").unwrap();
+
+        let doc = load(&path).unwrap();
+        assert_eq!(doc.title, "my notes");
+        assert_eq!(
+            doc.html,
+            "<p>Line one<br>\nline &lt;two&gt;</p>\n<p>Next</p>\n"
+        );
+        assert!(!doc.should_clean_html);
+        assert!(doc.url.starts_with("https://files.rr.invalid/"));
+        assert!(doc.url.ends_with("/my-notes.txt"));
+    }
+
+    #[test]
+    fn url_stays_the_same_when_the_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.md");
+        fs::write(&path, "# Draft").unwrap();
+        let before = load(&path).unwrap();
+        fs::write(&path, "# Final").unwrap();
+        let after = load(&path).unwrap();
+        assert_eq!(before.url, after.url);
+        assert_eq!(after.title, "Final");
+
+        let other = dir.path().join("sub");
+        fs::create_dir(&other).unwrap();
+        fs::write(other.join("report.md"), "# Draft").unwrap();
+        assert_ne!(load(&other.join("report.md")).unwrap().url, before.url);
+        // The same file through another path
+        assert_eq!(load(&other.join("../report.md")).unwrap().url, after.url);
+    }
+}
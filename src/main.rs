@@ -1,48 +1,92 @@
-mod cache;
 mod cli;
-mod client;
+mod export;
+mod import;
+mod local_file;
 mod output;
-mod types;
+mod paths;
+mod report;
+mod util;
 
-use std::io::{self, Write};
-use std::sync::Mutex;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use clap::Parser;
+use futures::{stream, StreamExt, TryStreamExt};
 use once_cell::sync::Lazy;
 
-use cache::Cache;
-use cli::{Args, Command, CreateArgs, ListArgs, UpdateArgs};
-use client::{DebugCache, ReaderClient};
-use types::*;
+use cli::{
+    Args, BackupArgs, CacheBackend, CacheCommand, Command, CreateArgs, ExportCommand,
+    ExportMarkdownArgs, GetArgs, ImportArgs, ListArgs, ListFilterArgs, QueueCommand, ReadArgs,
+    RestoreArgs, SyncArgs, UpdateArgs, CACHE_ENDPOINTS, DEFAULT_CACHE_TTLS,
+};
+use report::{
+    CreateCounts, CreateResult, CreateStatus, FlushResult, FlushStatus, RestoreReport, SaveFailure,
+};
+use rr::cache::{list_cache_key, tag_list_cache_key, MergeCounts, SyncState};
+use rr::types::*;
+use rr::{
+    BackupReader, BackupRecord, BackupWriter, Cache, DebugCache, QueuedOp, RateLimiter, RateLimits,
+    ReaderClient, ReaderError, RetryPolicy, WaitNotice, WriteQueue,
+};
 
-// Global state for cache file paths (used by signal handlers and panic hooks)
-static CACHE_PATHS: Lazy<Mutex<CachePaths>> = Lazy::new(|| {
-    Mutex::new(CachePaths {
-        cache_file: None,
-        debug_cache_file: None,
-    })
-});
+type SharedCache = Arc<Mutex<Cache>>;
+
+/// Documents `rr sync` merges into the store at a time
+const SYNC_BATCH: usize = 100;
+
+/// Documents `rr sync` fetches between saves of the store
+const SYNC_SAVE_EVERY: usize = 50 * SYNC_BATCH;
+
+// Live caches, reachable from the Ctrl-C handler and panic hook so they can
+// persist whatever was fetched before the interruption
+static LIVE_CACHES: Lazy<Mutex<LiveCaches>> = Lazy::new(|| Mutex::new(LiveCaches::default()));
 
-struct CachePaths {
-    cache_file: Option<String>,
-    debug_cache_file: Option<String>,
+#[derive(Default)]
+struct LiveCaches {
+    cache: Option<SharedCache>,
+    debug_cache: Option<Arc<Mutex<DebugCache>>>,
 }
 
-impl CachePaths {
-    fn save_all(&self) {
-        // Best-effort save - don't propagate errors in signal handlers
-        if let Some(path) = &self.cache_file {
-            let _ = Cache::save_if_exists(path).inspect_err(|e| {
+impl LiveCaches {
+    /// Best-effort save; doesn't propagate errors since it runs in handlers.
+    /// With `wait` false, caches locked elsewhere are skipped rather than
+    /// risking a deadlock (the panicking thread may hold the lock itself).
+    fn save_all(&self, wait: bool) {
+        if let Some(cache) = self.cache.as_ref().and_then(|c| lock_live(c, wait)) {
+            let _ = cache.save().inspect_err(|e| {
                 eprintln!("Warning: Failed to save cache: {}", e);
             });
         }
-        let _ = DebugCache::save_if_exists().inspect_err(|e| {
-            eprintln!("Warning: Failed to save debug cache: {}", e);
-        });
+        if let Some(cache) = self.debug_cache.as_ref().and_then(|c| lock_live(c, wait)) {
+            let _ = cache.save().inspect_err(|e| {
+                eprintln!("Warning: Failed to save debug cache: {}", e);
+            });
+        }
     }
 }
 
+/// Lock a cache shared with the handlers, ignoring poisoning: the state is
+/// still worth saving after a panic elsewhere
+fn lock_live<T>(mutex: &Mutex<T>, wait: bool) -> Option<MutexGuard<'_, T>> {
+    if wait {
+        return Some(mutex.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+fn lock_cache(cache: &SharedCache) -> MutexGuard<'_, Cache> {
+    cache.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 #[tokio::main]
 async fn main() {
     // Install panic hook first (before anything else)
@@ -53,16 +97,16 @@ async fn main() {
 
         // Try to save caches
         eprintln!("Attempting to save caches before panic exit...");
-        if let Ok(paths) = CACHE_PATHS.lock() {
-            paths.save_all();
+        if let Some(live) = lock_live(&LIVE_CACHES, false) {
+            live.save_all(false);
         }
     }));
 
     // Install Ctrl-C handler
     ctrlc::set_handler(move || {
         eprintln!("\nReceived interrupt signal. Saving caches...");
-        if let Ok(paths) = CACHE_PATHS.lock() {
-            paths.save_all();
+        if let Some(live) = lock_live(&LIVE_CACHES, true) {
+            live.save_all(true);
         }
         eprintln!("Caches saved. Exiting.");
         std::process::exit(130); // Standard exit code for SIGINT
@@ -73,56 +117,145 @@ async fn main() {
 
     if let Err(e) = run(args).await {
         eprintln!("{}", e);
+        std::process::exit(exit_code(&e));
+    }
+}
 
-        // Save caches on error exit
-        if let Ok(paths) = CACHE_PATHS.lock() {
-            paths.save_all();
-        }
+/// Map an error to the process exit code documented in the README
+fn exit_code(error: &anyhow::Error) -> i32 {
+    let reader_error = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ReaderError>());
 
-        std::process::exit(1);
+    match reader_error {
+        Some(ReaderError::InvalidConfig(_)) => 2,
+        Some(ReaderError::Unauthorized { .. }) => 3,
+        Some(ReaderError::NotFound { .. }) => 4,
+        Some(ReaderError::Validation { .. }) => 5,
+        Some(ReaderError::RateLimited { .. }) => 6,
+        Some(ReaderError::Network { .. }) => 7,
+        Some(ReaderError::Decode { .. }) => 8,
+        Some(ReaderError::Api { .. }) => 9,
+        None => 1,
     }
 }
 
 async fn run(args: Args) -> Result<()> {
-    let token = args
-        .token
-        .context("Missing API token. Set READWISE_ACCESS_TOKEN env var or use --token")?;
+    // Cache and queue management work offline and don't need a token
+    match &args.command {
+        Command::Cache(command) => return handle_cache(command, &args),
+        Command::Queue(command @ (QueueCommand::List | QueueCommand::Clear)) => {
+            return handle_queue(command, &args);
+        }
+        _ => {}
+    }
+
+    let token = args.token.clone().ok_or_else(|| {
+        ReaderError::InvalidConfig(
+            "missing API token. Set READWISE_ACCESS_TOKEN env var or use --token".to_string(),
+        )
+    })?;
 
-    let mut client = ReaderClient::new(&token, args.verbose)?;
+    let retry_policy = RetryPolicy {
+        max_attempts: args.max_attempts.max(1),
+        base_delay: Duration::from_millis(args.retry_delay_ms),
+        max_total_wait: Duration::from_secs(args.retry_max_wait),
+        ..RetryPolicy::default()
+    };
+    let rate_limiter = RateLimiter::new(RateLimits {
+        default_per_minute: args.rate_limit,
+        list_per_minute: args.list_rate_limit,
+        write_per_minute: args.write_rate_limit,
+    });
+    let mut client = ReaderClient::new(&token, &args.base_url, args.verbose)?
+        .with_retry_policy(retry_policy)
+        .with_rate_limiter(rate_limiter)
+        .with_max_rate_limit_wait(args.max_rate_limit_wait.map(Duration::from_secs))
+        .with_wait_notice(if args.json {
+            WaitNotice::Json
+        } else {
+            WaitNotice::Auto
+        });
 
-    // Register debug cache path if verbose mode
+    // Record requests and register the debug cache path if verbose mode
     if args.verbose {
-        if let Ok(mut paths) = CACHE_PATHS.lock() {
-            paths.debug_cache_file = Some("debug_cache.json".to_string());
+        let debug_file = args
+            .debug_file
+            .clone()
+            .unwrap_or_else(paths::default_debug_file);
+        client = client.with_debug_file(debug_file);
+        if let Some(mut live) = lock_live(&LIVE_CACHES, true) {
+            live.debug_cache = client.debug_cache().cloned();
         }
     }
 
-    let mut cache = if args.cache {
-        // Register cache path
-        if let Ok(mut paths) = CACHE_PATHS.lock() {
-            paths.cache_file = Some(args.cache_file.clone());
+    let cache = if args.cache && !args.no_cache {
+        let cache = Arc::new(Mutex::new(open_cache(&args)?));
+        if let Some(mut live) = lock_live(&LIVE_CACHES, true) {
+            live.cache = Some(Arc::clone(&cache));
         }
-        Some(Cache::new(&args.cache_file))
+        Some(cache)
     } else {
         None
     };
+    let cache = cache.as_ref();
+    let queue = write_queue(&args);
 
     let result = match args.command {
-        Command::Auth => handle_auth(&mut client, args.json).await,
-        Command::Create(create_args) => handle_create(&mut client, create_args, args.json).await,
-        Command::List(list_args) => {
-            handle_list(&mut client, list_args, args.json, &mut cache).await
+        Command::List(list_args) if args.offline => {
+            handle_list_offline(client.account_id(), list_args, args.json, cache)
+        }
+        Command::Get(get_args) if args.offline => {
+            handle_get_offline(client.account_id(), get_args, args.json, cache)
+        }
+        Command::Read(read_args) if args.offline => {
+            handle_read_offline(client.account_id(), read_args, args.json, cache)
+        }
+        Command::Export(ExportCommand::Markdown(export_args)) if args.offline => {
+            handle_export_markdown_offline(client.account_id(), export_args, args.json, cache)
+        }
+        Command::TagList if args.offline => {
+            handle_tag_list_offline(client.account_id(), args.json, cache)
+        }
+        Command::Create(create_args) => {
+            let writes = Writes::new(&queue, args.offline);
+            handle_create(&mut client, create_args, args.json, cache, writes).await
+        }
+        Command::Update(update_args) => {
+            let writes = Writes::new(&queue, args.offline);
+            handle_update(&mut client, update_args, args.json, cache, writes).await
         }
-        Command::Update(update_args) => handle_update(&mut client, update_args, args.json).await,
         Command::Delete(delete_args) => {
-            handle_delete(&mut client, &delete_args.id, args.json).await
+            let writes = Writes::new(&queue, args.offline);
+            handle_delete(&mut client, &delete_args.id, args.json, cache, writes).await
+        }
+        command if args.offline => Err(anyhow::anyhow!(
+            "rr {} needs the network and can't run with --offline",
+            command.name()
+        )),
+        Command::Auth => handle_auth(&mut client, args.json).await,
+        Command::List(list_args) => handle_list(&mut client, list_args, args.json, cache).await,
+        Command::Get(get_args) => handle_get(&mut client, get_args, args.json).await,
+        Command::Read(read_args) => handle_read(&mut client, read_args, args.json).await,
+        Command::TagList => handle_tag_list(&mut client, args.json, cache).await,
+        Command::Backup(backup_args) => handle_backup(&mut client, backup_args, args.json).await,
+        Command::Restore(restore_args) => {
+            handle_restore(&mut client, restore_args, args.json, cache).await
+        }
+        Command::Import(import_args) => {
+            handle_import(&mut client, import_args, args.json, cache).await
+        }
+        Command::Export(ExportCommand::Markdown(export_args)) => {
+            handle_export_markdown(&mut client, export_args, args.json, cache).await
         }
-        Command::TagList => handle_tag_list(&mut client, args.json, &mut cache).await,
+        Command::Sync(sync_args) => handle_sync(&mut client, sync_args, args.json, cache).await,
+        Command::Queue(_) => handle_queue_flush(&mut client, &queue, args.json, cache).await,
+        Command::Cache(_) => unreachable!("handled before the client is created"),
     };
 
     // Save cache if enabled
     if let Some(c) = cache {
-        c.save()?;
+        lock_cache(c).save()?;
     }
 
     // Save debug cache if verbose mode
@@ -131,8 +264,42 @@ async fn run(args: Args) -> Result<()> {
     result
 }
 
+/// Where `create`/`update`/`delete` send writes they can't make now
+#[derive(Clone, Copy)]
+struct Writes<'a> {
+    queue: &'a WriteQueue,
+    /// Queue without trying the network at all
+    offline: bool,
+}
+
+impl<'a> Writes<'a> {
+    fn new(queue: &'a WriteQueue, offline: bool) -> Self {
+        Self { queue, offline }
+    }
+
+    /// Journal `op` for `rr queue flush`, reporting the network `error` that
+    /// made it necessary, if any
+    fn enqueue(&self, op: QueuedOp, json_output: bool, error: Option<ReaderError>) -> Result<()> {
+        if let Some(e) = error {
+            eprintln!("{}", e);
+        }
+        let write = self.queue.push(op)?;
+        let pending = self.queue.load()?.len();
+        println!("{}", output::format_queued(&write, pending, json_output));
+        Ok(())
+    }
+}
+
+fn write_queue(args: &Args) -> WriteQueue {
+    WriteQueue::new(
+        args.queue_file
+            .clone()
+            .unwrap_or_else(paths::default_queue_file),
+    )
+}
+
 async fn fetch_with_cache<T, F, Fut>(
-    cache: &mut Option<Cache>,
+    cache: Option<&SharedCache>,
     cache_key: &str,
     endpoint: &str,
     params: serde_json::Value,
@@ -141,14 +308,13 @@ async fn fetch_with_cache<T, F, Fut>(
 where
     T: serde::de::DeserializeOwned + serde::Serialize,
     F: FnOnce() -> Fut,
-    Fut: std::future::Future<Output = Result<T>>,
+    Fut: std::future::Future<Output = Result<T, ReaderError>>,
 {
     // Try cache first
-    if let Some(c) = cache.as_ref() {
-        if let Some(entry) = c.get(cache_key) {
-            if let Ok(cached) = serde_json::from_value::<T>(entry.response.clone()) {
-                return Ok(cached);
-            }
+    let entry = cache.and_then(|c| lock_cache(c).get(cache_key));
+    if let Some(entry) = entry {
+        if let Ok(cached) = serde_json::from_value::<T>(entry.response) {
+            return Ok(cached);
         }
     }
 
@@ -156,14 +322,58 @@ where
     let result = fetch_fn().await?;
 
     // Update cache
-    if let Some(c) = cache.as_mut() {
+    if let Some(c) = cache {
         let response_json = serde_json::to_value(&result)?;
-        c.set(cache_key, endpoint, params, response_json);
+        lock_cache(c).set(cache_key, endpoint, params, response_json)?;
     }
 
     Ok(result)
 }
 
+fn cache_file(args: &Args) -> String {
+    args.cache_file
+        .clone()
+        .unwrap_or_else(|| paths::default_cache_file(args.cache_backend))
+}
+
+/// Open the cache selected by `--cache-backend`/`--cache-file` and apply TTLs
+fn open_cache(args: &Args) -> Result<Cache> {
+    let path = cache_file(args);
+    let mut cache = match args.cache_backend {
+        CacheBackend::Json => Cache::new(&path),
+        #[cfg(feature = "sqlite")]
+        CacheBackend::Sqlite => Cache::open_sqlite(&path)?,
+    };
+
+    for (endpoint, ttl) in DEFAULT_CACHE_TTLS {
+        cache.set_ttl(endpoint, *ttl);
+    }
+    for setting in &args.cache_ttl {
+        match &setting.endpoint {
+            Some(endpoint) => cache.set_ttl(endpoint, setting.ttl),
+            None => {
+                for endpoint in CACHE_ENDPOINTS {
+                    cache.set_ttl(endpoint, setting.ttl);
+                }
+            }
+        }
+    }
+    cache.set_refresh(args.refresh);
+    Ok(cache)
+}
+
+/// Drop cached listings that a successful write has made stale
+fn invalidate_after_write(cache: Option<&SharedCache>, tags_changed: bool) -> Result<()> {
+    if let Some(c) = cache {
+        let mut c = lock_cache(c);
+        c.invalidate_endpoint("list")?;
+        if tags_changed {
+            c.invalidate_endpoint("tag_list")?;
+        }
+    }
+    Ok(())
+}
+
 fn print_tag_not_found_warning(tag_name: &str, tags: &[Tag]) {
     eprintln!("Warning: Tag '{}' not found. Available tags:", tag_name);
     for tag in tags.iter().take(10) {
@@ -176,15 +386,15 @@ fn print_tag_not_found_warning(tag_name: &str, tags: &[Tag]) {
 
 async fn resolve_tag_key(
     client: &mut ReaderClient,
-    cache: &mut Option<Cache>,
+    cache: Option<&SharedCache>,
     tag_name: &str,
 ) -> Result<Option<String>> {
-    let cache_key = "tag_list:all";
+    let cache_key = tag_list_cache_key(client.account_id());
 
     // Fetch tags with caching
     let tags = fetch_with_cache(
         cache,
-        cache_key,
+        &cache_key,
         "tag_list",
         serde_json::json!({}),
         || async { client.list_all_tags().await },
@@ -219,9 +429,47 @@ async fn handle_create(
     client: &mut ReaderClient,
     args: CreateArgs,
     json_output: bool,
+    cache: Option<&SharedCache>,
+    writes: Writes<'_>,
 ) -> Result<()> {
-    let request = CreateDocumentRequest {
-        url: args.url,
+    let file = match &args.file {
+        Some(path) => Some(local_file::load(path)?),
+        None if args.url.is_none() => {
+            return handle_create_bulk(client, args, json_output, cache, writes).await;
+        }
+        None => None,
+    };
+    let tags_changed = args.tags.is_some();
+    let mut request = create_request(args);
+    // Flags given alongside --file take precedence over what it implies
+    if let Some(file) = file {
+        if request.url.is_empty() {
+            request.url = file.url;
+        }
+        request.html = Some(file.html);
+        request.should_clean_html = request.should_clean_html.or(Some(file.should_clean_html));
+        request.title = request.title.or(Some(file.title));
+    }
+
+    if writes.offline {
+        return writes.enqueue(QueuedOp::Create { request }, json_output, None);
+    }
+    let response = match client.create_document(request.clone()).await {
+        Ok(response) => response,
+        Err(e @ ReaderError::Network { .. }) => {
+            return writes.enqueue(QueuedOp::Create { request }, json_output, Some(e));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    invalidate_after_write(cache, tags_changed)?;
+    println!("{}", output::format_create_response(&response, json_output));
+    Ok(())
+}
+
+/// The save request described by `create`'s flags
+fn create_request(args: CreateArgs) -> CreateDocumentRequest {
+    CreateDocumentRequest {
+        url: args.url.unwrap_or_default(),
         html: args.html,
         should_clean_html: args.should_clean_html,
         title: args.title,
@@ -234,22 +482,181 @@ async fn handle_create(
         saved_using: args.saved_using,
         tags: args.tags,
         notes: args.notes,
+    }
+}
+
+/// One line of a `create --from-file`/`--stdin` list: a URL, or a JSON save
+/// request
+fn parse_create_line(line: &str) -> Result<CreateDocumentRequest, String> {
+    let request = if line.starts_with('{') {
+        serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?
+    } else {
+        CreateDocumentRequest {
+            url: line.to_string(),
+            ..Default::default()
+        }
     };
+    if !is_http_url(&request.url) {
+        return Err(format!("not an http(s) URL: {}", request.url));
+    }
+    Ok(request)
+}
 
-    let response = client.create_document(request).await?;
-    println!("{}", output::format_create_response(&response, json_output));
-    Ok(())
+/// Whether `url` is an absolute `http://` or `https://` URL with a host
+fn is_http_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    let Some(rest) = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    !host.is_empty() && !url.chars().any(char::is_whitespace)
 }
 
-fn build_list_cache_key(params: &ListDocumentsParams, page_num: usize) -> String {
-    format!(
-        "list:{}:{}:{}:{}:page:{}",
-        params.location.as_deref().unwrap_or("all"),
-        params.category.as_deref().unwrap_or("all"),
-        params.tag.as_deref().unwrap_or("all"),
-        params.id.as_deref().unwrap_or("all"),
-        page_num
-    )
+/// Fill in what a listed document leaves unset from the command-line flags;
+/// `--tags` are added to its own
+fn apply_create_defaults(request: &mut CreateDocumentRequest, args: &CreateArgs) {
+    if request.location.is_none() {
+        request.location = args.location.as_ref().map(|l| l.to_string());
+    }
+    if request.category.is_none() {
+        request.category = args.category.as_ref().map(|c| c.to_string());
+    }
+    if request.saved_using.is_none() {
+        request.saved_using = args.saved_using.clone();
+    }
+    if let Some(tags) = &args.tags {
+        let own = request.tags.get_or_insert_with(Vec::new);
+        for tag in tags {
+            if !own.contains(tag) {
+                own.push(tag.clone());
+            }
+        }
+    }
+}
+
+async fn handle_create_bulk(
+    client: &ReaderClient,
+    args: CreateArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+    writes: Writes<'_>,
+) -> Result<()> {
+    let input = match &args.from_file {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .context("Failed to read stdin")?;
+            input
+        }
+    };
+
+    let mut counts = CreateCounts::default();
+    let mut report = |result: CreateResult| {
+        counts.record(result.status);
+        println!("{}", output::format_create_result(&result, json_output));
+    };
+
+    let mut requests = vec![];
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_create_line(line) {
+            Ok(mut request) => {
+                apply_create_defaults(&mut request, &args);
+                requests.push((idx + 1, request));
+            }
+            Err(detail) => report(CreateResult {
+                line: idx + 1,
+                url: None,
+                status: CreateStatus::Failed,
+                id: None,
+                detail: Some(detail),
+            }),
+        }
+    }
+
+    let mut tags_changed = false;
+    if writes.offline {
+        for (line, request) in requests {
+            let url = request.url.clone();
+            writes.queue.push(QueuedOp::Create { request })?;
+            report(CreateResult {
+                line,
+                url: Some(url),
+                status: CreateStatus::Queued,
+                id: None,
+                detail: None,
+            });
+        }
+    } else {
+        let mut saves = stream::iter(requests)
+            .map(|(line, request)| {
+                let mut client = client.clone();
+                async move {
+                    let response = client.create_document(request.clone()).await;
+                    (line, request, response)
+                }
+            })
+            .buffer_unordered(usize::from(args.concurrency));
+
+        // Report each line as its save finishes, so results can come out of order
+        while let Some((line, request, response)) = saves.next().await {
+            let url = Some(request.url.clone());
+            let result = match response {
+                Ok(response) => {
+                    tags_changed |= request.tags.is_some();
+                    CreateResult {
+                        line,
+                        url,
+                        status: CreateStatus::Created,
+                        id: Some(response.id),
+                        detail: None,
+                    }
+                }
+                Err(e @ ReaderError::Network { .. }) => {
+                    writes.queue.push(QueuedOp::Create { request })?;
+                    CreateResult {
+                        line,
+                        url,
+                        status: CreateStatus::Queued,
+                        id: None,
+                        detail: Some(e.to_string()),
+                    }
+                }
+                Err(e) => CreateResult {
+                    line,
+                    url,
+                    status: CreateStatus::Failed,
+                    id: None,
+                    detail: Some(e.to_string()),
+                },
+            };
+            report(result);
+        }
+    }
+
+    if counts.created > 0 {
+        invalidate_after_write(cache, tags_changed)?;
+    }
+    if !json_output {
+        println!("{}", output::format_create_summary(&counts));
+    }
+    if counts.failed > 0 {
+        anyhow::bail!(
+            "{} of {} documents could not be saved",
+            counts.failed,
+            counts.created + counts.queued + counts.failed
+        );
+    }
+    Ok(())
 }
 
 fn prompt_for_next_page() -> Result<bool> {
@@ -281,38 +688,24 @@ async fn handle_list(
     client: &mut ReaderClient,
     args: ListArgs,
     json_output: bool,
-    cache: &mut Option<Cache>,
+    cache: Option<&SharedCache>,
 ) -> Result<()> {
     // Resolve tag name to tag key if --tag was provided
-    let tag_key = if let Some(tag_name) = &args.tag {
+    let tag_key = if let Some(tag_name) = &args.filter.tag {
         resolve_tag_key(client, cache, tag_name).await?
     } else {
         None
     };
 
-    let mut params = ListDocumentsParams {
-        id: args.id,
-        updated_after: args.updated_after,
-        location: args.location.map(|l| l.to_string()),
-        category: args.category.map(|c| c.to_string()),
-        tag: tag_key,
-        page_cursor: args.cursor,
-        with_html_content: args.with_html_content,
-        with_raw_source_url: args.with_raw_source_url,
-    };
+    let mut params = list_params(&args, tag_key);
 
     let mut page_num = 1;
 
     loop {
         // Fetch page with caching
-        let cache_key = build_list_cache_key(&params, page_num);
-        let params_json = serde_json::json!({
-            "location": params.location,
-            "category": params.category,
-            "tag": params.tag,
-            "id": params.id,
-            "page": page_num
-        });
+        let cache_key = list_cache_key(client.account_id(), &params);
+        let mut params_json = serde_json::to_value(&params)?;
+        params_json["page"] = page_num.into();
 
         let response = fetch_with_cache(cache, &cache_key, "list", params_json, || async {
             client.list_documents(&params).await
@@ -322,6 +715,14 @@ async fn handle_list(
         // Print page results
         print_page_results(&response, page_num, json_output);
 
+        // Persist each page of a full listing, so an interrupted run can pick
+        // up from the cached pages (keyed by cursor) when repeated
+        if args.all {
+            if let Some(c) = cache {
+                lock_cache(c).save()?;
+            }
+        }
+
         // Handle pagination
         match response.next_page_cursor {
             Some(cursor) => {
@@ -348,11 +749,550 @@ async fn handle_list(
     Ok(())
 }
 
+fn list_params(args: &ListArgs, tag_key: Option<String>) -> ListDocumentsParams {
+    ListDocumentsParams {
+        page_cursor: args.cursor.clone(),
+        with_html_content: args.with_html_content,
+        with_raw_source_url: args.with_raw_source_url,
+        ..filter_params(&args.filter, tag_key)
+    }
+}
+
+fn filter_params(filter: &ListFilterArgs, tag_key: Option<String>) -> ListDocumentsParams {
+    ListDocumentsParams {
+        id: filter.id.clone(),
+        updated_after: filter.updated_after.clone(),
+        location: filter.location.as_ref().map(|l| l.to_string()),
+        category: filter.category.as_ref().map(|c| c.to_string()),
+        tag: tag_key,
+        ..Default::default()
+    }
+}
+
+/// The cache, which offline commands and syncs can't do without
+fn local_store<'a>(cache: Option<&'a SharedCache>, what: &str) -> Result<&'a SharedCache> {
+    cache.ok_or_else(|| {
+        ReaderError::InvalidConfig(format!(
+            "{} uses the cache and can't run with --no-cache",
+            what
+        ))
+        .into()
+    })
+}
+
+/// Tags known locally: the cached tag list (however old), or else the tags
+/// of stored documents
+fn offline_tags(cache: &Cache, account: &str) -> Result<Vec<Tag>> {
+    let cached = cache
+        .entry(&tag_list_cache_key(account))
+        .and_then(|entry| serde_json::from_value::<Vec<Tag>>(entry.response).ok());
+    if let Some(tags) = cached {
+        return Ok(tags);
+    }
+
+    let mut tags = BTreeMap::new();
+    for doc in cache.documents(account)? {
+        for tag in doc.tags() {
+            tags.entry(tag.key.clone()).or_insert(tag);
+        }
+    }
+    let mut tags: Vec<Tag> = tags.into_values().collect();
+    tags.sort_by_key(|tag| tag.name.to_lowercase());
+    Ok(tags)
+}
+
+/// Offline counterpart of [`resolve_tag_key`]
+fn offline_tag_key(cache: &Cache, account: &str, tag_name: &str) -> Result<Option<String>> {
+    let tags = offline_tags(cache, account)?;
+    let tag_key = tags
+        .iter()
+        .find(|tag| tag.name.eq_ignore_ascii_case(tag_name))
+        .map(|tag| tag.key.clone());
+    if tag_key.is_none() {
+        print_tag_not_found_warning(tag_name, &tags);
+    }
+    Ok(tag_key)
+}
+
+fn handle_list_offline(
+    account: &str,
+    args: ListArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    let cache = lock_cache(local_store(cache, "rr list --offline")?);
+
+    let tag_key = match &args.filter.tag {
+        Some(tag_name) => offline_tag_key(&cache, account, tag_name)?,
+        None => None,
+    };
+    let params = list_params(&args, tag_key);
+
+    let stored = cache.document_count(account)?;
+    if stored == 0 {
+        eprintln!("Warning: No documents stored locally; run `rr sync` while online first");
+    }
+
+    let results = cache.find_documents(account, &params)?;
+
+    let response = ListDocumentsResponse {
+        count: results.len() as u32,
+        next_page_cursor: None,
+        results,
+    };
+    print_page_results(&response, 1, json_output);
+    if !json_output {
+        eprintln!("--- End of results (offline, {} stored) ---", stored);
+    }
+    Ok(())
+}
+
+fn handle_tag_list_offline(
+    account: &str,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    let cache = lock_cache(local_store(cache, "rr tag-list --offline")?);
+    let tags = offline_tags(&cache, account)?;
+    println!("{}", output::format_tags_response(&tags, json_output));
+    Ok(())
+}
+
+async fn handle_get(client: &mut ReaderClient, args: GetArgs, json_output: bool) -> Result<()> {
+    let doc = match client.get_document(&args.id, args.with_html_content).await {
+        Ok(doc) => doc,
+        Err(e @ ReaderError::NotFound { .. }) => {
+            return Err(anyhow::Error::new(e).context(format!("Document not found: {}", args.id)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    println!("{}", output::format_document_detail(&doc, json_output));
+    Ok(())
+}
+
+fn handle_get_offline(
+    account: &str,
+    args: GetArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    let cache = lock_cache(local_store(cache, "rr get --offline")?);
+    let mut doc = cache.document(account, &args.id)?.with_context(|| {
+        format!(
+            "Document not found in the local store: {} (run `rr sync` while online)",
+            args.id
+        )
+    })?;
+    if !args.with_html_content {
+        doc.html_content = None;
+    }
+    println!("{}", output::format_document_detail(&doc, json_output));
+    Ok(())
+}
+
+async fn handle_read(client: &mut ReaderClient, args: ReadArgs, json_output: bool) -> Result<()> {
+    let doc = match client.get_document(&args.id, true).await {
+        Ok(doc) => doc,
+        Err(e @ ReaderError::NotFound { .. }) => {
+            return Err(anyhow::Error::new(e).context(format!("Document not found: {}", args.id)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    show_article(&doc, &args, json_output)
+}
+
+fn handle_read_offline(
+    account: &str,
+    args: ReadArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    let doc = lock_cache(local_store(cache, "rr read --offline")?)
+        .document(account, &args.id)?
+        .with_context(|| {
+            format!(
+                "Document not found in the local store: {} (run `rr sync --with-html-content` while online)",
+                args.id
+            )
+        })?;
+    show_article(&doc, &args, json_output)
+}
+
+/// Render `doc` for reading and send it to the pager when stdout is a terminal
+fn show_article(doc: &Document, args: &ReadArgs, json_output: bool) -> Result<()> {
+    let tty = io::stdout().is_terminal();
+    let width = args.width.unwrap_or_else(|| {
+        std::env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(80)
+    });
+    let text = output::format_article(doc, width.max(20), tty, json_output)?;
+
+    if json_output || !tty || args.no_pager {
+        println!("{}", text);
+        return Ok(());
+    }
+    page_text(&text)
+}
+
+/// Pipe `text` through `$PAGER` (default `less -R`), printing it directly if
+/// `$PAGER` is empty or can't be started
+fn page_text(text: &str) -> Result<()> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
+    if pager.trim().is_empty() {
+        println!("{}", text);
+        return Ok(());
+    }
+
+    let mut command = std::process::Command::new("sh");
+    command.arg("-c").arg(&pager).stdin(Stdio::piped());
+    // Like git: let less quit on short documents and pass colour escapes through
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(_) => {
+            println!("{}", text);
+            return Ok(());
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The reader may quit the pager before reaching the end
+        match writeln!(stdin, "{}", text) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                return Err(e).context("Failed to write to the pager")
+            }
+            _ => {}
+        }
+    }
+    child
+        .wait()
+        .with_context(|| format!("Failed to run pager `{}`", pager))?;
+    Ok(())
+}
+
+/// Overwrite a one-line progress message on stderr, if it's a terminal
+fn show_progress(json_output: bool, message: &str) {
+    if !json_output && io::stderr().is_terminal() {
+        eprint!("\r{}", message);
+        io::stderr().flush().ok();
+    }
+}
+
+/// End the line [`show_progress`] has been overwriting
+fn end_progress(json_output: bool) {
+    if !json_output && io::stderr().is_terminal() {
+        eprintln!();
+    }
+}
+
+async fn handle_backup(
+    client: &mut ReaderClient,
+    args: BackupArgs,
+    json_output: bool,
+) -> Result<()> {
+    let mut backup = BackupWriter::create(&args.out, args.with_html_content)?;
+
+    let mut tags = 0;
+    let mut tag_stream = client.tags_stream();
+    while let Some(tag) = tag_stream.try_next().await? {
+        backup.write(&BackupRecord::Tag(tag))?;
+        tags += 1;
+    }
+    drop(tag_stream);
+
+    let params = ListDocumentsParams {
+        with_html_content: args.with_html_content.then_some(true),
+        ..Default::default()
+    };
+    let mut documents = 0;
+    let mut doc_stream = client.documents_stream(params);
+    while let Some(doc) = doc_stream.try_next().await? {
+        backup.write(&BackupRecord::Document(Box::new(doc)))?;
+        documents += 1;
+        show_progress(json_output, &format!("Backed up {} documents", documents));
+    }
+    drop(doc_stream);
+    end_progress(json_output);
+
+    backup.finish()?;
+    println!(
+        "{}",
+        output::format_backup_report(documents, tags, &args.out, json_output)
+    );
+    Ok(())
+}
+
+/// Request recreating `doc` from a backup, or `None` if it has no source URL
+///
+/// `url` isn't a fallback: it points into Reader, at a document that may no
+/// longer exist.
+fn restore_request(doc: Document) -> Option<CreateDocumentRequest> {
+    let tags = doc
+        .tags()
+        .into_iter()
+        .map(|tag| tag.name)
+        .collect::<Vec<_>>();
+    Some(CreateDocumentRequest {
+        url: doc.source_url?,
+        // Saved HTML keeps the document readable even if its source is gone;
+        // Reader already cleaned it once
+        should_clean_html: doc.html_content.as_ref().map(|_| false),
+        html: doc.html_content,
+        title: doc.title,
+        author: doc.author,
+        summary: doc.summary,
+        published_date: doc.published_date,
+        image_url: doc.image_url,
+        location: doc.location,
+        category: doc.category,
+        saved_using: None,
+        tags: Some(tags).filter(|tags| !tags.is_empty()),
+        notes: None,
+    })
+}
+
+async fn handle_restore(
+    client: &mut ReaderClient,
+    args: RestoreArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    // Check the backup before spending requests on the library listing
+    let backup = BackupReader::open(&args.file)?;
+
+    show_progress(json_output, "Listing documents already in Reader...");
+    let mut existing = HashSet::new();
+    let mut documents = client.documents_stream(ListDocumentsParams::default());
+    while let Some(doc) = documents.try_next().await? {
+        existing.extend(doc.source_url);
+        existing.extend(doc.url);
+    }
+    drop(documents);
+    end_progress(json_output);
+
+    let mut report = RestoreReport::default();
+    let mut stopped_by = None;
+    let mut tags_changed = false;
+    for record in backup {
+        let BackupRecord::Document(doc) = record? else {
+            continue;
+        };
+        // Highlights and notes belong to a parent document and can't be saved
+        if doc.parent_id.is_some() {
+            report.skipped_children += 1;
+            continue;
+        }
+        let id = doc.id.clone();
+        let Some(request) = restore_request(*doc) else {
+            report.failed.push(SaveFailure {
+                url: id,
+                error: "no source URL".to_string(),
+            });
+            continue;
+        };
+        if existing.contains(&request.url) {
+            report.existing += 1;
+            continue;
+        }
+
+        let url = request.url.clone();
+        let has_tags = request.tags.is_some();
+        match client.create_document(request).await {
+            Ok(_) => {
+                report.created += 1;
+                tags_changed |= has_tags;
+                existing.insert(url);
+            }
+            // Everything not yet created is skipped as existing on a re-run
+            Err(e) if e.aborts_batch() => {
+                stopped_by = Some(e);
+                break;
+            }
+            Err(e) => report.failed.push(SaveFailure {
+                url,
+                error: e.to_string(),
+            }),
+        }
+        show_progress(
+            json_output,
+            &format!(
+                "Restored {} documents ({} already in Reader)",
+                report.created, report.existing
+            ),
+        );
+    }
+    end_progress(json_output);
+
+    if report.created > 0 {
+        invalidate_after_write(cache, tags_changed)?;
+    }
+    println!(
+        "{}",
+        output::format_restore_report(&report, stopped_by.is_some(), json_output)
+    );
+    match stopped_by {
+        Some(e) => Err(anyhow::Error::new(e).context("Restore stopped; run it again to resume")),
+        None => Ok(()),
+    }
+}
+
+async fn handle_import(
+    client: &mut ReaderClient,
+    args: ImportArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    let input = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("Failed to read {}", args.file.display()))?;
+    let requests = import::parse(args.format, &input)
+        .with_context(|| format!("Failed to parse {}", args.file.display()))?;
+    let checkpoint_path = args
+        .checkpoint
+        .clone()
+        .unwrap_or_else(|| paths::default_import_checkpoint(&util::input_sha256(&input)));
+    let mut checkpoint = import::Checkpoint::load(&checkpoint_path, &input)?;
+    let total = requests.len();
+    if checkpoint.next > 0 && !json_output {
+        eprintln!(
+            "Resuming at bookmark {} of {} from {}",
+            checkpoint.next + 1,
+            total,
+            checkpoint_path.display()
+        );
+    }
+
+    let mut created = false;
+    let mut tags_changed = false;
+    let mut stopped_by = None;
+    for (index, request) in requests.into_iter().enumerate().skip(checkpoint.next) {
+        let url = request.url.clone();
+        let has_tags = request.tags.is_some();
+        match client.create_document(request).await {
+            Ok(_) => {
+                checkpoint.created += 1;
+                created = true;
+                tags_changed |= has_tags;
+            }
+            // Stop here; the checkpoint resumes with this bookmark next time
+            Err(e) if e.aborts_batch() => {
+                stopped_by = Some(e);
+                break;
+            }
+            Err(e) => checkpoint.failed.push(SaveFailure {
+                url,
+                error: e.to_string(),
+            }),
+        }
+        checkpoint.next = index + 1;
+        checkpoint.save(&checkpoint_path)?;
+        show_progress(
+            json_output,
+            &format!("Imported {} of {} bookmarks", checkpoint.next, total),
+        );
+    }
+    end_progress(json_output);
+
+    if created {
+        invalidate_after_write(cache, tags_changed)?;
+    }
+    if stopped_by.is_none() {
+        match std::fs::remove_file(&checkpoint_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(e).with_context(|| {
+                    format!("Failed to remove checkpoint {}", checkpoint_path.display())
+                })
+            }
+            _ => {}
+        }
+    }
+    println!(
+        "{}",
+        output::format_import_report(&checkpoint, total, stopped_by.is_some(), json_output)
+    );
+    match stopped_by {
+        Some(e) => Err(anyhow::Error::new(e).context(format!(
+            "Import stopped at bookmark {} of {}; run it again to resume",
+            checkpoint.next + 1,
+            total
+        ))),
+        None => Ok(()),
+    }
+}
+
+async fn handle_export_markdown(
+    client: &mut ReaderClient,
+    args: ExportMarkdownArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    // Unlike `list`, an unknown tag must not widen the export to everything
+    let tag_key = match &args.filter.tag {
+        Some(tag_name) => Some(
+            resolve_tag_key(client, cache, tag_name)
+                .await?
+                .with_context(|| format!("Tag not found: {}", tag_name))?,
+        ),
+        None => None,
+    };
+    let params = ListDocumentsParams {
+        with_html_content: Some(true),
+        ..filter_params(&args.filter, tag_key)
+    };
+
+    let mut export = export::MarkdownExport::open(&args.dir)?;
+    let mut documents = client.documents_stream(params);
+    while let Some(doc) = documents.try_next().await? {
+        export.write(&doc)?;
+    }
+    println!(
+        "{}",
+        output::format_export_report(export.counts(), &args.dir, json_output)
+    );
+    Ok(())
+}
+
+fn handle_export_markdown_offline(
+    account: &str,
+    args: ExportMarkdownArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    let cache = lock_cache(local_store(cache, "rr export --offline")?);
+    let tag_key = match &args.filter.tag {
+        Some(tag_name) => Some(
+            offline_tag_key(&cache, account, tag_name)?
+                .with_context(|| format!("Tag not found: {}", tag_name))?,
+        ),
+        None => None,
+    };
+    let params = ListDocumentsParams {
+        with_html_content: Some(true),
+        ..filter_params(&args.filter, tag_key)
+    };
+
+    let mut export = export::MarkdownExport::open(&args.dir)?;
+    for doc in &cache.find_documents(account, &params)? {
+        export.write(doc)?;
+    }
+    println!(
+        "{}",
+        output::format_export_report(export.counts(), &args.dir, json_output)
+    );
+    Ok(())
+}
+
 async fn handle_update(
     client: &mut ReaderClient,
     args: UpdateArgs,
     json_output: bool,
+    cache: Option<&SharedCache>,
+    writes: Writes<'_>,
 ) -> Result<()> {
+    let tags_changed = args.tags.is_some();
     let request = UpdateDocumentRequest {
         title: args.title,
         author: args.author,
@@ -365,13 +1305,41 @@ async fn handle_update(
         tags: args.tags,
     };
 
-    let response = client.update_document(&args.id, request).await?;
+    let id = args.id;
+    if writes.offline {
+        return writes.enqueue(QueuedOp::Update { id, request }, json_output, None);
+    }
+    let response = match client.update_document(&id, request.clone()).await {
+        Ok(response) => response,
+        Err(e @ ReaderError::Network { .. }) => {
+            return writes.enqueue(QueuedOp::Update { id, request }, json_output, Some(e));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    invalidate_after_write(cache, tags_changed)?;
     println!("{}", output::format_update_response(&response, json_output));
     Ok(())
 }
 
-async fn handle_delete(client: &mut ReaderClient, id: &str, json_output: bool) -> Result<()> {
-    client.delete_document(id).await?;
+async fn handle_delete(
+    client: &mut ReaderClient,
+    id: &str,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+    writes: Writes<'_>,
+) -> Result<()> {
+    let op = || QueuedOp::Delete { id: id.to_string() };
+    if writes.offline {
+        return writes.enqueue(op(), json_output, None);
+    }
+    match client.delete_document(id).await {
+        Ok(()) => {}
+        Err(e @ ReaderError::Network { .. }) => {
+            return writes.enqueue(op(), json_output, Some(e));
+        }
+        Err(e) => return Err(e.into()),
+    }
+    invalidate_after_write(cache, false)?;
     println!("{}", output::format_delete_response(id, json_output));
     Ok(())
 }
@@ -379,14 +1347,14 @@ async fn handle_delete(client: &mut ReaderClient, id: &str, json_output: bool) -
 async fn handle_tag_list(
     client: &mut ReaderClient,
     json_output: bool,
-    cache: &mut Option<Cache>,
+    cache: Option<&SharedCache>,
 ) -> Result<()> {
-    let cache_key = "tag_list:all";
+    let cache_key = tag_list_cache_key(client.account_id());
 
     // Fetch tags with caching
     let tags = fetch_with_cache(
         cache,
-        cache_key,
+        &cache_key,
         "tag_list",
         serde_json::json!({}),
         || async { client.list_all_tags().await },
@@ -396,3 +1364,382 @@ async fn handle_tag_list(
     println!("{}", output::format_tags_response(&tags, json_output));
     Ok(())
 }
+
+async fn handle_sync(
+    client: &mut ReaderClient,
+    args: SyncArgs,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    let cache = local_store(cache, "rr sync")?;
+    let account = client.account_id().to_string();
+    let mut state = if args.full {
+        SyncState::default()
+    } else {
+        lock_cache(cache).sync_state(&account)?
+    };
+    let since = state.updated_at.clone();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let reconcile = args.reconcile
+        || state
+            .reconciled_at
+            .is_none_or(|at| now.saturating_sub(at) >= args.reconcile_every.as_secs());
+    // A sync from scratch sees every id anyway, so it reconciles for free
+    let mut seen_ids = since.is_none().then(HashSet::new);
+
+    let params = ListDocumentsParams {
+        updated_after: since.clone(),
+        with_html_content: args.with_html_content.then_some(true),
+        ..ListDocumentsParams::default()
+    };
+    let mut counts = MergeCounts::default();
+    let mut fetched = 0;
+    let mut batch = Vec::with_capacity(SYNC_BATCH);
+
+    // The high-water mark only moves once every document is in, so an
+    // interrupted sync starts over from the old one
+    let mut documents = client.documents_stream(params);
+    while let Some(doc) = documents.try_next().await? {
+        state.updated_at = state.updated_at.take().max(doc.updated_at.clone());
+        if let Some(ids) = seen_ids.as_mut() {
+            ids.insert(doc.id.clone());
+        }
+        batch.push(doc);
+        fetched += 1;
+        show_progress(json_output, &format!("Fetched {} documents", fetched));
+
+        if batch.len() == SYNC_BATCH {
+            let mut c = lock_cache(cache);
+            counts += merge_synced(&mut c, &account, &mut batch)?;
+            // Saving rewrites the whole JSON store, so only do it now and then;
+            // the Ctrl-C handler saves whatever was merged since
+            if fetched % SYNC_SAVE_EVERY == 0 {
+                c.save()?;
+            }
+        }
+    }
+    drop(documents);
+    end_progress(json_output);
+    counts += merge_synced(&mut lock_cache(cache), &account, &mut batch)?;
+
+    let server_ids = match seen_ids {
+        Some(ids) => Some(ids),
+        None if reconcile => {
+            if !json_output {
+                eprintln!("Reconciling: listing every document id...");
+            }
+            Some(
+                client
+                    .documents_stream(ListDocumentsParams::default())
+                    .map_ok(|doc| doc.id)
+                    .try_collect::<HashSet<_>>()
+                    .await?,
+            )
+        }
+        None => None,
+    };
+
+    // Tombstone stored documents that no longer exist in Reader
+    let removed = match server_ids {
+        Some(server_ids) => {
+            let missing = lock_cache(cache).reconcile_documents(&account, &server_ids)?;
+            state.reconciled_at = Some(now);
+            Some(missing)
+        }
+        None => None,
+    };
+
+    state.synced_at = Some(now);
+    let total = {
+        let mut c = lock_cache(cache);
+        c.set_sync_state(&account, &state)?;
+        c.save()?;
+        c.document_count(&account)?
+    };
+
+    println!(
+        "{}",
+        output::format_sync_report(
+            &counts,
+            total,
+            since.as_deref(),
+            removed.as_deref(),
+            json_output
+        )
+    );
+    Ok(())
+}
+
+/// Store synced documents, emptying `batch`
+///
+/// Documents fetched without `--with-html-content` keep the HTML stored by an
+/// earlier sync, so `rr read --offline` still works after a quick sync.
+fn merge_synced(
+    cache: &mut Cache,
+    account: &str,
+    batch: &mut Vec<Document>,
+) -> Result<MergeCounts> {
+    for doc in batch.iter_mut().filter(|doc| doc.html_content.is_none()) {
+        if let Some(stored) = cache.document(account, &doc.id)? {
+            doc.html_content = stored.html_content;
+        }
+    }
+    let counts = cache.merge_documents(account, batch)?;
+    batch.clear();
+    Ok(counts)
+}
+
+/// Send one queued write, returning a detail worth reporting on success
+async fn replay(client: &mut ReaderClient, op: &QueuedOp) -> Result<Option<String>, ReaderError> {
+    match op {
+        QueuedOp::Create { request } => {
+            let response = client.create_document(request.clone()).await?;
+            Ok(Some(format!("saved as {}", response.id)))
+        }
+        QueuedOp::Update { id, request } => {
+            client.update_document(id, request.clone()).await?;
+            Ok(None)
+        }
+        QueuedOp::Delete { id } => {
+            client.delete_document(id).await?;
+            Ok(None)
+        }
+    }
+}
+
+async fn handle_queue_flush(
+    client: &mut ReaderClient,
+    queue: &WriteQueue,
+    json_output: bool,
+    cache: Option<&SharedCache>,
+) -> Result<()> {
+    let _lock = queue.lock()?;
+    let mut pending = queue.load()?.into_iter();
+    let mut results = vec![];
+    let mut remaining = vec![];
+    let mut stopped_by = None;
+    let mut applied = false;
+    let mut tags_changed = false;
+
+    for write in pending.by_ref() {
+        let op = write.op.to_string();
+        let (status, detail) = match replay(client, &write.op).await {
+            Ok(detail) => {
+                applied = true;
+                tags_changed |= write.op.changes_tags();
+                (FlushStatus::Applied, detail)
+            }
+            Err(ReaderError::NotFound { .. }) => {
+                let detail = match write.op {
+                    QueuedOp::Delete { .. } => "already deleted in Reader",
+                    _ => "document no longer exists in Reader",
+                };
+                (FlushStatus::Conflict, Some(detail.to_string()))
+            }
+            // Keep this and every later write, in order, for the next flush
+            Err(e) if e.aborts_batch() => {
+                results.push(FlushResult {
+                    op,
+                    status: FlushStatus::Pending,
+                    detail: Some(e.to_string()),
+                });
+                remaining.push(write);
+                stopped_by = Some(e);
+                break;
+            }
+            Err(e) => (FlushStatus::Failed, Some(e.to_string())),
+        };
+        results.push(FlushResult { op, status, detail });
+    }
+    for write in pending {
+        results.push(FlushResult {
+            op: write.op.to_string(),
+            status: FlushStatus::Pending,
+            detail: None,
+        });
+        remaining.push(write);
+    }
+
+    queue.replace(&remaining)?;
+    if applied {
+        invalidate_after_write(cache, tags_changed)?;
+    }
+    println!("{}", output::format_flush_report(&results, json_output));
+
+    match stopped_by {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
+fn handle_queue(command: &QueueCommand, args: &Args) -> Result<()> {
+    let queue = write_queue(args);
+    match command {
+        QueueCommand::List => {
+            println!("{}", output::format_queue(&queue.load()?, args.json));
+        }
+        QueueCommand::Clear => {
+            let _lock = queue.lock()?;
+            let count = queue.load()?.len();
+            queue.replace(&[])?;
+            println!("{}", output::format_queue_cleared(count, args.json));
+        }
+        QueueCommand::Flush => unreachable!("flushing needs the client"),
+    }
+    Ok(())
+}
+
+fn handle_cache(command: &CacheCommand, args: &Args) -> Result<()> {
+    let json_output = args.json;
+    let mut cache = open_cache(args)?;
+
+    match command {
+        CacheCommand::Stats => {
+            let stats = cache.stats()?;
+            println!(
+                "{}",
+                output::format_cache_stats(cache.file_path(), &stats, json_output)
+            );
+        }
+        CacheCommand::List(args) => {
+            let endpoint = args.endpoint.as_ref().map(|e| e.to_string());
+            let mut entries: Vec<_> = cache
+                .entries()?
+                .into_iter()
+                .filter(|(_, entry)| endpoint.as_ref().is_none_or(|e| &entry.endpoint == e))
+                .collect();
+            entries
+                .sort_by(|(a_key, a), (b_key, b)| (a.timestamp, a_key).cmp(&(b.timestamp, b_key)));
+            println!("{}", output::format_cache_entries(&entries, json_output));
+        }
+        CacheCommand::Show(args) => {
+            let entry = cache
+                .entry(&args.key)
+                .with_context(|| format!("No cache entry with key '{}'", args.key))?;
+            println!(
+                "{}",
+                output::format_cache_entry(&args.key, &entry, json_output)
+            );
+        }
+        CacheCommand::Prune(args) => {
+            let removed = cache.prune_older_than(args.older_than)?;
+            cache.save()?;
+            println!("{}", output::format_cache_removed(removed, json_output));
+        }
+        CacheCommand::Clear(args) => {
+            let removed = match &args.endpoint {
+                Some(endpoint) => cache.invalidate_endpoint(&endpoint.to_string())?,
+                None => cache.clear()?,
+            };
+            cache.save()?;
+            println!("{}", output::format_cache_removed(removed, json_output));
+        }
+        CacheCommand::Migrate(migrate_args) => {
+            let from = migrate_args
+                .from
+                .clone()
+                .unwrap_or_else(|| paths::default_cache_file(CacheBackend::Json));
+            if Path::new(&from) == Path::new(cache.file_path()) {
+                anyhow::bail!("Cannot migrate {} into itself", from);
+            }
+            let imported = cache.import_json(&from)?;
+            cache.save()?;
+            println!(
+                "{}",
+                output::format_cache_migrated(imported, &from, json_output)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_args(flags: &[&str]) -> CreateArgs {
+        let argv = ["rr", "create", "--stdin"].iter().chain(flags);
+        match Args::try_parse_from(argv).unwrap().command {
+            Command::Create(args) => args,
+            command => panic!("parsed {:?}", command),
+        }
+    }
+
+    #[test]
+    fn create_line_takes_a_url() {
+        let request = parse_create_line("https://example.com/a?b#c").unwrap();
+        assert_eq!(request.url, "https://example.com/a?b#c");
+        assert!(request.title.is_none());
+        assert!(parse_create_line("HTTP://example.com").is_ok());
+    }
+
+    #[test]
+    fn create_line_takes_a_json_request() {
+        let request =
+            parse_create_line(r#"{"url": "https://example.com", "title": "T", "tags": ["a"]}"#)
+                .unwrap();
+        assert_eq!(request.title.as_deref(), Some("T"));
+        assert_eq!(request.tags, Some(vec!["a".to_string()]));
+    }
+
+    #[test]
+    fn create_line_rejects_anything_else() {
+        for line in [
+            "not json {",
+            "example.com",
+            "ftp://example.com/file",
+            "https://",
+            "https:///path",
+            "https://exa mple.com",
+            r#"{"url": "javascript:alert(1)"}"#,
+        ] {
+            assert!(parse_create_line(line).is_err(), "{}", line);
+        }
+        let error = parse_create_line(r#"{"title": "no url"}"#).unwrap_err();
+        assert!(error.starts_with("invalid JSON"), "{}", error);
+    }
+
+    #[test]
+    fn create_defaults_fill_unset_fields_and_add_tags() {
+        let args = create_args(&[
+            "--location",
+            "later",
+            "--category",
+            "article",
+            "--saved-using",
+            "rr",
+            "--tags",
+            "a,b",
+        ]);
+
+        let mut request = parse_create_line("https://example.com").unwrap();
+        apply_create_defaults(&mut request, &args);
+        assert_eq!(request.location.as_deref(), Some("later"));
+        assert_eq!(request.category.as_deref(), Some("article"));
+        assert_eq!(request.saved_using.as_deref(), Some("rr"));
+        assert_eq!(request.tags, Some(vec!["a".to_string(), "b".to_string()]));
+
+        let mut request = parse_create_line(
+            r#"{"url": "https://example.com", "location": "archive", "category": "pdf",
+                "saved_using": "script", "tags": ["b", "c"]}"#,
+        )
+        .unwrap();
+        apply_create_defaults(&mut request, &args);
+        assert_eq!(request.location.as_deref(), Some("archive"));
+        assert_eq!(request.category.as_deref(), Some("pdf"));
+        assert_eq!(request.saved_using.as_deref(), Some("script"));
+        assert_eq!(
+            request.tags,
+            Some(vec!["b".to_string(), "c".to_string(), "a".to_string()])
+        );
+
+        let mut request = parse_create_line("https://example.com").unwrap();
+        apply_create_defaults(&mut request, &create_args(&[]));
+        assert!(request.location.is_none());
+        assert!(request.tags.is_none());
+    }
+}
@@ -1,4 +1,14 @@
-use crate::types::*;
+use std::path::Path;
+
+use crate::export::ExportCounts;
+use crate::import::Checkpoint;
+use crate::report::{
+    CreateCounts, CreateResult, CreateStatus, FlushCounts, FlushResult, FlushStatus, RestoreReport,
+};
+use crate::util::{format_timestamp, text_to_html};
+use rr::cache::{CacheEntry, CacheStats, ImportCounts, MergeCounts};
+use rr::queue::QueuedWrite;
+use rr::types::*;
 
 pub fn format_auth_success(json_output: bool) -> String {
     if json_output {
@@ -27,6 +37,42 @@ pub fn format_create_response(response: &CreateDocumentResponse, json_output: bo
     }
 }
 
+pub fn format_create_result(result: &CreateResult, json_output: bool) -> String {
+    if json_output {
+        return serde_json::to_string(result).unwrap_or_default();
+    }
+
+    let status = match result.status {
+        CreateStatus::Created => "created",
+        CreateStatus::Queued => "queued",
+        CreateStatus::Failed => "failed",
+    };
+    let mut output = format!("line {}: {}", result.line, status);
+    if let Some(url) = &result.url {
+        output.push_str(&format!(" {}", url));
+    }
+    if let Some(id) = &result.id {
+        output.push_str(&format!(" ({})", id));
+    }
+    if let Some(detail) = &result.detail {
+        output.push_str(&format!(": {}", detail));
+    }
+    output
+}
+
+/// One-line summary of a bulk `create`, pointing at `rr queue flush` when
+/// lines were queued
+pub fn format_create_summary(counts: &CreateCounts) -> String {
+    let mut output = format!("{} created, {} failed", counts.created, counts.failed);
+    if counts.queued > 0 {
+        output.push_str(&format!(
+            ", {} queued; run `rr queue flush` once back online",
+            counts.queued
+        ));
+    }
+    output
+}
+
 pub fn format_list_response(response: &ListDocumentsResponse, json_output: bool) -> String {
     if json_output {
         serde_json::to_string(response).unwrap_or_default()
@@ -71,6 +117,152 @@ fn format_document(doc: &Document) -> String {
     output
 }
 
+pub fn format_document_detail(doc: &Document, json_output: bool) -> String {
+    if json_output {
+        return serde_json::to_string(doc).unwrap_or_default();
+    }
+
+    let mut output = format!("{}\n", doc.title.as_deref().unwrap_or("(no title)"));
+    let mut field = |label: &str, value: Option<String>| {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            output.push_str(&format!("  {:<17} {}\n", format!("{}:", label), value));
+        }
+    };
+
+    field("ID", Some(doc.id.clone()));
+    field("URL", doc.url.clone());
+    field("Source URL", doc.source_url.clone());
+    field("Author", doc.author.clone());
+    field("Site", doc.site_name.clone());
+    field("Source", doc.source.clone());
+    field("Category", doc.category.clone());
+    field("Location", doc.location.clone());
+    field("Tags", doc.tags.as_ref().map(format_tags));
+    field("Word count", doc.word_count.map(|n| n.to_string()));
+    field(
+        "Reading progress",
+        doc.reading_progress.map(|p| format!("{:.0}%", p * 100.0)),
+    );
+    field("Published", doc.published_date.clone());
+    field("Created", doc.created_at.clone());
+    field("Updated", doc.updated_at.clone());
+    field("Saved", doc.saved_at.clone());
+    field("First opened", doc.first_opened_at.clone());
+    field("Last opened", doc.last_opened_at.clone());
+    field("Last moved", doc.last_moved_at.clone());
+    field("Parent ID", doc.parent_id.clone());
+    field("Image URL", doc.image_url.clone());
+    for (key, value) in doc.extra.iter().flatten() {
+        let value = match value {
+            serde_json::Value::Null => continue,
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        field(key, Some(value));
+    }
+
+    for (label, text) in [
+        ("Summary", &doc.summary),
+        ("Content", &doc.content),
+        ("HTML content", &doc.html_content),
+    ] {
+        if let Some(text) = text.as_deref().filter(|t| !t.is_empty()) {
+            output.push_str(&format!("\n{}:\n{}\n", label, text.trim_end()));
+        }
+    }
+
+    output.trim_end().to_string()
+}
+
+/// Render a document's content as wrapped text for reading, with ANSI styling
+/// when `styled`
+///
+/// Uses `html_content` when present, falling back to the plain `content` or
+/// the summary. Links become numbered footnotes listed after the text.
+pub fn format_article(
+    doc: &Document,
+    width: usize,
+    styled: bool,
+    json_output: bool,
+) -> anyhow::Result<String> {
+    let html = if let Some(html) = doc.html_content.as_ref().filter(|h| !h.trim().is_empty()) {
+        html.clone()
+    } else if let Some(text) = [&doc.content, &doc.summary]
+        .into_iter()
+        .flatten()
+        .find(|t| !t.trim().is_empty())
+    {
+        text_to_html(text)
+    } else {
+        anyhow::bail!("Document {} has no content to read", doc.id);
+    };
+
+    let config = html2text::config::rich().link_footnotes(true);
+    let body = if styled && !json_output {
+        config.coloured(html.as_bytes(), width, ansi_style)?
+    } else {
+        config.string_from_read(html.as_bytes(), width)?
+    };
+
+    if json_output {
+        return Ok(serde_json::json!({
+            "id": doc.id,
+            "title": doc.title,
+            "author": doc.author,
+            "url": doc.source_url.as_ref().or(doc.url.as_ref()),
+            "text": body.trim_end(),
+        })
+        .to_string());
+    }
+
+    let title = doc.title.as_deref().unwrap_or("(no title)");
+    let mut output = if styled {
+        format!("\x1b[1m{}\x1b[0m\n", title)
+    } else {
+        format!("{}\n", title)
+    };
+    let byline: Vec<&str> = [&doc.author, &doc.site_name]
+        .into_iter()
+        .filter_map(|s| s.as_deref().filter(|s| !s.is_empty()))
+        .collect();
+    if !byline.is_empty() {
+        output.push_str(&format!("{}\n", byline.join(" · ")));
+    }
+    if let Some(url) = doc.source_url.as_ref().or(doc.url.as_ref()) {
+        output.push_str(&format!("{}\n", url));
+    }
+    output.push_str(&format!(
+        "{}\n\n",
+        "─".repeat(width.min(title.chars().count().max(20)))
+    ));
+    output.push_str(body.trim_end());
+    Ok(output)
+}
+
+/// Terminal escapes for html2text's rich annotations
+fn ansi_style(annotations: &[html2text::render::RichAnnotation], text: &str) -> String {
+    use html2text::render::RichAnnotation;
+
+    let mut codes = Vec::new();
+    for annotation in annotations {
+        match annotation {
+            RichAnnotation::Strong => codes.push("1"),
+            RichAnnotation::Emphasis => codes.push("3"),
+            RichAnnotation::Strikeout => codes.push("9"),
+            RichAnnotation::Link(_) => codes.push("4;34"),
+            RichAnnotation::Image(_) => codes.push("35"),
+            RichAnnotation::Code | RichAnnotation::Preformat(_) => codes.push("36"),
+            _ => {}
+        }
+    }
+    codes.dedup();
+    if codes.is_empty() {
+        text.to_string()
+    } else {
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
 fn format_tags(tags: &serde_json::Value) -> String {
     match tags {
         serde_json::Value::Array(arr) => arr
@@ -111,3 +303,312 @@ pub fn format_tags_response(tags: &[Tag], json_output: bool) -> String {
             .join("\n")
     }
 }
+
+pub fn format_cache_stats(file_path: &str, stats: &CacheStats, json_output: bool) -> String {
+    if json_output {
+        let mut value = serde_json::to_value(stats).unwrap_or_default();
+        value["file"] = file_path.into();
+        value.to_string()
+    } else {
+        let mut output = format!("Cache file: {}\n", file_path);
+        output.push_str(&format!(
+            "Entries: {} ({})\n",
+            stats.entries,
+            format_size(stats.size_bytes)
+        ));
+        for (endpoint, endpoint_stats) in &stats.endpoints {
+            output.push_str(&format!(
+                "  {}: {} ({})\n",
+                endpoint,
+                endpoint_stats.entries,
+                format_size(endpoint_stats.size_bytes)
+            ));
+        }
+        if let Some(documents) = stats.documents {
+            output.push_str(&format!("Documents: {}\n", documents));
+        }
+        if let (Some(oldest), Some(newest)) = (stats.oldest, stats.newest) {
+            output.push_str(&format!("Oldest: {}\n", format_timestamp(oldest)));
+            output.push_str(&format!("Newest: {}", format_timestamp(newest)));
+        }
+        output.trim_end().to_string()
+    }
+}
+
+pub fn format_cache_entries(entries: &[(String, CacheEntry)], json_output: bool) -> String {
+    if json_output {
+        let list: Vec<_> = entries
+            .iter()
+            .map(|(key, entry)| {
+                serde_json::json!({
+                    "key": key,
+                    "endpoint": entry.endpoint,
+                    "timestamp": entry.timestamp,
+                    "params": entry.params,
+                })
+            })
+            .collect();
+        serde_json::to_string(&list).unwrap_or_default()
+    } else {
+        entries
+            .iter()
+            .map(|(key, entry)| format!("{}  {}", format_timestamp(entry.timestamp), key))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub fn format_cache_entry(key: &str, entry: &CacheEntry, json_output: bool) -> String {
+    if json_output {
+        serde_json::to_string(entry).unwrap_or_default()
+    } else {
+        format!(
+            "Key: {}\nEndpoint: {}\nCached: {}\nParams: {}\nResponse:\n{}",
+            key,
+            entry.endpoint,
+            format_timestamp(entry.timestamp),
+            entry.params,
+            serde_json::to_string_pretty(&entry.response).unwrap_or_default()
+        )
+    }
+}
+
+pub fn format_cache_removed(count: usize, json_output: bool) -> String {
+    if json_output {
+        format!(r#"{{"removed": {}}}"#, count)
+    } else {
+        format!("Removed {} cache entries", count)
+    }
+}
+
+pub fn format_cache_migrated(counts: ImportCounts, from: &str, json_output: bool) -> String {
+    if json_output {
+        serde_json::json!({
+            "imported": counts.entries,
+            "documents": counts.documents,
+            "from": from,
+        })
+        .to_string()
+    } else {
+        format!(
+            "Imported {} cache entries and {} documents from {}",
+            counts.entries, counts.documents, from
+        )
+    }
+}
+
+pub fn format_sync_report(
+    counts: &MergeCounts,
+    total: usize,
+    since: Option<&str>,
+    removed: Option<&[String]>,
+    json_output: bool,
+) -> String {
+    if json_output {
+        serde_json::json!({
+            "new": counts.new,
+            "changed": counts.changed,
+            "unchanged": counts.unchanged,
+            "documents": total,
+            "updated_after": since,
+            "reconciled": removed.is_some(),
+            "removed": removed.unwrap_or_default(),
+        })
+        .to_string()
+    } else {
+        let scope = match since {
+            Some(since) => format!("since {}", since),
+            None => "full sync".to_string(),
+        };
+        let mut output = format!(
+            "Synced ({}): {} new, {} changed, {} unchanged; {} documents stored",
+            scope, counts.new, counts.changed, counts.unchanged, total
+        );
+        if let Some(removed) = removed {
+            output.push_str(&format!(
+                "\nReconciled with Reader: {} deleted there",
+                removed.len()
+            ));
+            for id in removed {
+                output.push_str(&format!("\n  - {}", id));
+            }
+        }
+        output
+    }
+}
+
+pub fn format_queued(write: &QueuedWrite, pending: usize, json_output: bool) -> String {
+    if json_output {
+        serde_json::json!({"queued": true, "write": write, "pending": pending}).to_string()
+    } else {
+        format!(
+            "Queued {} ({} pending); run `rr queue flush` once back online",
+            write.op, pending
+        )
+    }
+}
+
+pub fn format_queue(writes: &[QueuedWrite], json_output: bool) -> String {
+    if json_output {
+        return serde_json::to_string(writes).unwrap_or_default();
+    }
+    if writes.is_empty() {
+        return "No queued writes".to_string();
+    }
+    writes
+        .iter()
+        .enumerate()
+        .map(|(idx, write)| {
+            format!(
+                "{:>3}. {} (queued {})",
+                idx + 1,
+                write.op,
+                format_timestamp(write.queued_at)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn format_queue_cleared(count: usize, json_output: bool) -> String {
+    if json_output {
+        serde_json::json!({"removed": count}).to_string()
+    } else {
+        format!("Removed {} queued writes", count)
+    }
+}
+
+pub fn format_flush_report(results: &[FlushResult], json_output: bool) -> String {
+    if json_output {
+        return serde_json::to_string(results).unwrap_or_default();
+    }
+    if results.is_empty() {
+        return "No queued writes".to_string();
+    }
+    let mut output = String::new();
+    let mut counts = FlushCounts::default();
+    for result in results {
+        let label = match result.status {
+            FlushStatus::Applied => "applied",
+            FlushStatus::Conflict => "conflict",
+            FlushStatus::Failed => "failed",
+            FlushStatus::Pending => "pending",
+        };
+        counts.record(&result.status);
+        output.push_str(&format!("{:<8} {}", label, result.op));
+        if let Some(detail) = &result.detail {
+            output.push_str(&format!(": {}", detail));
+        }
+        output.push('\n');
+    }
+    output.push_str(&format!(
+        "{} applied, {} conflicts, {} failed, {} still queued",
+        counts.applied, counts.conflicts, counts.failed, counts.pending
+    ));
+    output
+}
+
+pub fn format_export_report(counts: ExportCounts, dir: &Path, json_output: bool) -> String {
+    if json_output {
+        return serde_json::json!({
+            "dir": dir,
+            "new": counts.new,
+            "updated": counts.updated,
+            "unchanged": counts.unchanged,
+        })
+        .to_string();
+    }
+    format!(
+        "Exported {} documents to {}: {} new, {} updated, {} unchanged",
+        counts.new + counts.updated + counts.unchanged,
+        dir.display(),
+        counts.new,
+        counts.updated,
+        counts.unchanged
+    )
+}
+
+pub fn format_backup_report(
+    documents: usize,
+    tags: usize,
+    path: &Path,
+    json_output: bool,
+) -> String {
+    if json_output {
+        return serde_json::json!({
+            "file": path,
+            "documents": documents,
+            "tags": tags,
+        })
+        .to_string();
+    }
+    format!(
+        "Backed up {} documents and {} tags to {}",
+        documents,
+        tags,
+        path.display()
+    )
+}
+
+pub fn format_restore_report(report: &RestoreReport, stopped: bool, json_output: bool) -> String {
+    if json_output {
+        let mut value = serde_json::to_value(report).unwrap_or_default();
+        value["complete"] = (!stopped).into();
+        return value.to_string();
+    }
+
+    let mut output = String::new();
+    for failure in &report.failed {
+        output.push_str(&format!("  failed    {}: {}\n", failure.url, failure.error));
+    }
+    output.push_str(&format!(
+        "{}{} created, {} already in Reader, {} highlights/notes skipped, {} failed",
+        if stopped { "Stopped early: " } else { "" },
+        report.created,
+        report.existing,
+        report.skipped_children,
+        report.failed.len()
+    ));
+    output
+}
+
+pub fn format_import_report(
+    checkpoint: &Checkpoint,
+    total: usize,
+    stopped: bool,
+    json_output: bool,
+) -> String {
+    if json_output {
+        return serde_json::json!({
+            "total": total,
+            "processed": checkpoint.next,
+            "created": checkpoint.created,
+            "failed": checkpoint.failed,
+            "complete": !stopped,
+        })
+        .to_string();
+    }
+
+    let mut output = String::new();
+    for failure in &checkpoint.failed {
+        output.push_str(&format!("  failed    {}: {}\n", failure.url, failure.error));
+    }
+    output.push_str(&format!(
+        "{}{} of {} bookmarks saved, {} failed",
+        if stopped { "Stopped early: " } else { "" },
+        checkpoint.created,
+        total,
+        checkpoint.failed.len()
+    ));
+    output
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
@@ -0,0 +1,57 @@
+//! Default file locations, following the XDG base directory spec
+
+use std::env;
+use std::path::PathBuf;
+
+use crate::cli::CacheBackend;
+
+/// `$XDG_CACHE_HOME/rr`, falling back to `~/.cache/rr`
+pub fn cache_dir() -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache").join("rr")
+}
+
+/// `$XDG_STATE_HOME/rr`, falling back to `~/.local/state/rr`
+pub fn state_dir() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME", ".local/state").join("rr")
+}
+
+/// Cache file used for `backend` when `--cache-file` is not given
+pub fn default_cache_file(backend: CacheBackend) -> String {
+    cache_dir()
+        .join(backend.file_name())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Journal of offline writes used when `--queue-file` is not given
+pub fn default_queue_file() -> PathBuf {
+    state_dir().join("queue.jsonl")
+}
+
+/// Progress of importing the export file whose SHA-256 is `input_sha256`,
+/// used when `--checkpoint` is not given
+pub fn default_import_checkpoint(input_sha256: &str) -> PathBuf {
+    state_dir()
+        .join("imports")
+        .join(format!("{}.json", &input_sha256[..16]))
+}
+
+/// Debug log used when `--debug-file` is not given
+pub fn default_debug_file() -> PathBuf {
+    state_dir().join("debug_cache.json")
+}
+
+/// Base directory from `var` if it holds an absolute path (relative values
+/// are ignored, as the spec requires), else `$HOME/<fallback>`
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    if let Some(dir) = env::var_os(var).map(PathBuf::from) {
+        if dir.is_absolute() {
+            return dir;
+        }
+    }
+    match env::var_os("HOME") {
+        Some(home) if !home.is_empty() => PathBuf::from(home).join(fallback),
+        // No home directory: keep files next to the working directory
+        _ => PathBuf::from("."),
+    }
+}
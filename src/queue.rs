@@ -0,0 +1,250 @@
+//! Durable journal of writes made while Reader was unreachable
+//!
+//! Each queued write is one JSON line, appended and synced to disk before
+//! [`WriteQueue::push`] returns. Replaying is up to the caller: take the
+//! [`WriteQueue::lock`], [`WriteQueue::load`] the pending writes, send them in
+//! order and [`WriteQueue::replace`] the journal with whatever is left.
+
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::atomic_file::{lock_file, write_atomic};
+use crate::cache::now_secs;
+use crate::types::{CreateDocumentRequest, UpdateDocumentRequest};
+
+/// A write operation waiting to be sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum QueuedOp {
+    Create {
+        request: CreateDocumentRequest,
+    },
+    Update {
+        id: String,
+        request: UpdateDocumentRequest,
+    },
+    Delete {
+        id: String,
+    },
+}
+
+impl QueuedOp {
+    /// Whether replaying the operation may change the tag list
+    pub fn changes_tags(&self) -> bool {
+        match self {
+            QueuedOp::Create { request } => request.tags.is_some(),
+            QueuedOp::Update { request, .. } => request.tags.is_some(),
+            QueuedOp::Delete { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for QueuedOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueuedOp::Create { request } => write!(f, "create {}", request.url),
+            QueuedOp::Update { id, .. } => write!(f, "update {}", id),
+            QueuedOp::Delete { id } => write!(f, "delete {}", id),
+        }
+    }
+}
+
+/// One line of the journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedWrite {
+    /// Unix time (seconds) the write was queued
+    pub queued_at: u64,
+    #[serde(flatten)]
+    pub op: QueuedOp,
+}
+
+/// Append-only journal of [`QueuedWrite`]s in a JSON Lines file
+pub struct WriteQueue {
+    file_path: PathBuf,
+}
+
+impl WriteQueue {
+    /// Use the journal at `path`; nothing is read or created until needed
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            file_path: path.into(),
+        }
+    }
+
+    /// Path of the journal file
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Append `op` to the journal, durably
+    pub fn push(&self, op: QueuedOp) -> Result<QueuedWrite> {
+        let write = QueuedWrite {
+            queued_at: now_secs(),
+            op,
+        };
+        let mut line = serde_json::to_string(&write)?;
+        line.push('\n');
+
+        let _lock = self.lock()?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)
+            .with_context(|| format!("Failed to open queue {}", self.file_path.display()))?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        Ok(write)
+    }
+
+    /// Pending writes, oldest first; empty if the journal doesn't exist
+    pub fn load(&self) -> Result<Vec<QueuedWrite>> {
+        let content = match fs::read_to_string(&self.file_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read queue {}", self.file_path.display()))
+            }
+        };
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!(
+                        "Invalid entry on line {} of queue {}",
+                        idx + 1,
+                        self.file_path.display()
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Atomically rewrite the journal to hold only `remaining`
+    ///
+    /// Hold the [`WriteQueue::lock`] from [`WriteQueue::load`] until this
+    /// returns, or writes queued in between are lost.
+    pub fn replace(&self, remaining: &[QueuedWrite]) -> Result<()> {
+        let mut content = String::new();
+        for write in remaining {
+            content.push_str(&serde_json::to_string(write)?);
+            content.push('\n');
+        }
+        write_atomic(&self.file_path, content.as_bytes())
+    }
+
+    /// Take an exclusive advisory lock on `<queue file>.lock`, released when
+    /// the returned file is dropped
+    pub fn lock(&self) -> Result<File> {
+        if let Some(dir) = self
+            .file_path
+            .parent()
+            .filter(|d| !d.as_os_str().is_empty())
+        {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create queue directory {}", dir.display()))?;
+        }
+        lock_file(&self.file_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(url: &str) -> QueuedOp {
+        QueuedOp::Create {
+            request: CreateDocumentRequest {
+                url: url.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn ops(writes: &[QueuedWrite]) -> Vec<String> {
+        writes.iter().map(|write| write.op.to_string()).collect()
+    }
+
+    #[test]
+    fn missing_journal_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = WriteQueue::new(dir.path().join("queue.jsonl"));
+        assert!(queue.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn pushed_writes_load_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = WriteQueue::new(dir.path().join("state").join("queue.jsonl"));
+        queue.push(create("https://example.com/a")).unwrap();
+        queue
+            .push(QueuedOp::Delete {
+                id: "d1".to_string(),
+            })
+            .unwrap();
+
+        let writes = queue.load().unwrap();
+        assert_eq!(ops(&writes), ["create https://example.com/a", "delete d1"]);
+        assert!(writes[0].queued_at > 0);
+        assert!(!writes[0].op.changes_tags());
+    }
+
+    #[test]
+    fn flush_keeps_what_was_not_sent() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = WriteQueue::new(dir.path().join("queue.jsonl"));
+        for url in [
+            "https://example.com/a",
+            "https://example.com/b",
+            "https://example.com/c",
+        ] {
+            queue.push(create(url)).unwrap();
+        }
+
+        // The first write went through, the second hit a network error, so it
+        // and everything after it stay queued in order
+        {
+            let _lock = queue.lock().unwrap();
+            let writes = queue.load().unwrap();
+            queue.replace(&writes[1..]).unwrap();
+        }
+        assert_eq!(
+            ops(&queue.load().unwrap()),
+            [
+                "create https://example.com/b",
+                "create https://example.com/c"
+            ]
+        );
+
+        queue.push(create("https://example.com/d")).unwrap();
+        assert_eq!(queue.load().unwrap().len(), 3);
+
+        {
+            let _lock = queue.lock().unwrap();
+            queue.replace(&[]).unwrap();
+        }
+        assert!(queue.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn corrupt_line_is_reported_with_its_number() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = WriteQueue::new(dir.path().join("queue.jsonl"));
+        queue.push(create("https://example.com/a")).unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(queue.file_path())
+            .unwrap();
+        file.write_all(b"\n{\"op\": \"create\"\n").unwrap();
+
+        let error = queue.load().unwrap_err();
+        assert!(error.to_string().contains("line 3"), "{}", error);
+    }
+}
@@ -0,0 +1,277 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use tokio::time::sleep;
+
+/// Longest `Retry-After` honoured, in seconds; longer values are clamped so
+/// they can't overflow deadlines
+pub(crate) const MAX_RETRY_AFTER_SECS: u64 = 24 * 60 * 60;
+
+/// Which of Reader's per-token rate-limit budgets a request draws from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    /// `GET /v3/list/`
+    List,
+    /// `POST /v3/save/` and `PATCH /v3/update/`
+    Write,
+    /// Every other endpoint
+    Default,
+}
+
+/// Requests per minute allowed for each [`Budget`]; `0` disables limiting
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    pub default_per_minute: u32,
+    pub list_per_minute: u32,
+    pub write_per_minute: u32,
+}
+
+impl Default for RateLimits {
+    /// Reader's documented limits: 20/min by default, 50/min for create and update
+    fn default() -> Self {
+        Self {
+            default_per_minute: 20,
+            list_per_minute: 20,
+            write_per_minute: 50,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+    blocked_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn new(per_minute: u32) -> Self {
+        let capacity = per_minute as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: Instant::now(),
+            blocked_until: None,
+        }
+    }
+
+    /// Take a token, or return how long to wait before trying again
+    fn try_take(&mut self, now: Instant) -> Option<Duration> {
+        if let Some(until) = self.blocked_until {
+            if until > now {
+                return Some(until - now);
+            }
+            self.blocked_until = None;
+        }
+
+        if self.capacity == 0.0 {
+            return None;
+        }
+
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+
+    fn block_for(&mut self, now: Instant, duration: Duration) {
+        let until = now + duration.min(Duration::from_secs(MAX_RETRY_AFTER_SECS));
+        if self.blocked_until.is_none_or(|current| current < until) {
+            self.blocked_until = Some(until);
+        }
+        self.tokens = 0.0;
+        self.last_refill = until.max(self.last_refill);
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    default: Bucket,
+    list: Bucket,
+    write: Bucket,
+}
+
+impl Buckets {
+    fn get(&mut self, budget: Budget) -> &mut Bucket {
+        match budget {
+            Budget::Default => &mut self.default,
+            Budget::List => &mut self.list,
+            Budget::Write => &mut self.write,
+        }
+    }
+}
+
+/// Client-side token-bucket limiter
+///
+/// Cloning is cheap and clones share the same budgets, so one limiter can be
+/// handed to several [`ReaderClient`](crate::ReaderClient)s running concurrently.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            buckets: Arc::new(Mutex::new(Buckets {
+                default: Bucket::new(limits.default_per_minute),
+                list: Bucket::new(limits.list_per_minute),
+                write: Bucket::new(limits.write_per_minute),
+            })),
+        }
+    }
+
+    /// Wait until a request against `budget` is allowed
+    pub async fn acquire(&self, budget: Budget) {
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+                buckets.get(budget).try_take(Instant::now())
+            };
+            match wait {
+                Some(duration) => sleep(duration).await,
+                None => return,
+            }
+        }
+    }
+
+    /// Hold back every request against `budget` for `duration`, e.g. after
+    /// the server answered 429
+    pub fn pause(&self, budget: Budget, duration: Duration) {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        buckets.get(budget).block_for(Instant::now(), duration);
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimits::default())
+    }
+}
+
+/// Parse a `Retry-After` header given either as seconds or as an HTTP date
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<u64> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs);
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .map(|d| d.as_secs() + 1)
+            .unwrap_or(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_take_allows_a_burst_up_to_capacity() {
+        let mut bucket = Bucket::new(3);
+        let now = bucket.last_refill;
+        for _ in 0..3 {
+            assert_eq!(bucket.try_take(now), None);
+        }
+        // One token refills every 20 seconds at 3/min
+        let wait = bucket.try_take(now).unwrap();
+        assert!((wait.as_secs_f64() - 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn try_take_refills_over_time_without_exceeding_capacity() {
+        let mut bucket = Bucket::new(60);
+        let start = bucket.last_refill;
+        for _ in 0..60 {
+            assert_eq!(bucket.try_take(start), None);
+        }
+        assert!(bucket.try_take(start).is_some());
+        assert_eq!(bucket.try_take(start + Duration::from_secs(1)), None);
+
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..60 {
+            assert_eq!(bucket.try_take(later), None);
+        }
+        assert!(bucket.try_take(later).is_some());
+    }
+
+    #[test]
+    fn zero_capacity_never_limits() {
+        let mut bucket = Bucket::new(0);
+        let now = bucket.last_refill;
+        for _ in 0..1000 {
+            assert_eq!(bucket.try_take(now), None);
+        }
+    }
+
+    #[test]
+    fn block_for_holds_back_requests_until_it_ends() {
+        let mut bucket = Bucket::new(60);
+        let now = bucket.last_refill;
+        bucket.block_for(now, Duration::from_secs(30));
+        assert_eq!(bucket.try_take(now), Some(Duration::from_secs(30)));
+        assert_eq!(
+            bucket.try_take(now + Duration::from_secs(10)),
+            Some(Duration::from_secs(20))
+        );
+        // Tokens start refilling only once the block is over
+        assert!(bucket.try_take(now + Duration::from_secs(30)).is_some());
+        assert_eq!(bucket.try_take(now + Duration::from_secs(31)), None);
+    }
+
+    #[test]
+    fn block_for_keeps_the_longer_block() {
+        let mut bucket = Bucket::new(60);
+        let now = bucket.last_refill;
+        bucket.block_for(now, Duration::from_secs(30));
+        bucket.block_for(now, Duration::from_secs(5));
+        assert_eq!(bucket.try_take(now), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn block_for_clamps_huge_durations() {
+        let mut bucket = Bucket::new(60);
+        let now = bucket.last_refill;
+        bucket.block_for(now, Duration::MAX);
+        assert_eq!(
+            bucket.try_take(now),
+            Some(Duration::from_secs(MAX_RETRY_AFTER_SECS))
+        );
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_http_dates() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, " 42 ".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(42));
+
+        let later = SystemTime::now() + Duration::from_secs(90);
+        headers.insert(RETRY_AFTER, httpdate::fmt_http_date(later).parse().unwrap());
+        let secs = parse_retry_after(&headers).unwrap();
+        assert!((89..=91).contains(&secs), "{}", secs);
+
+        headers.insert(
+            RETRY_AFTER,
+            "Sun, 06 Nov 1994 08:49:37 GMT".parse().unwrap(),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(0));
+
+        headers.insert(RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+}
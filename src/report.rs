@@ -0,0 +1,105 @@
+//! Outcomes of commands that save or replay many documents, printed by
+//! [`crate::output`]
+
+use serde::{Deserialize, Serialize};
+
+/// Outcome of saving one line of a `create --from-file`/`--stdin` list
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreateStatus {
+    Created,
+    /// Reader was unreachable; queued for `rr queue flush`
+    Queued,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateResult {
+    /// Line number in the input
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub status: CreateStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// How many lines of a bulk `create` ended in each [`CreateStatus`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CreateCounts {
+    pub created: usize,
+    pub queued: usize,
+    pub failed: usize,
+}
+
+impl CreateCounts {
+    pub fn record(&mut self, status: CreateStatus) {
+        match status {
+            CreateStatus::Created => self.created += 1,
+            CreateStatus::Queued => self.queued += 1,
+            CreateStatus::Failed => self.failed += 1,
+        }
+    }
+}
+
+/// Outcome of replaying one queued write
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlushStatus {
+    Applied,
+    /// The document was deleted in Reader meanwhile; the write was dropped
+    Conflict,
+    /// Rejected by Reader; the write was dropped
+    Failed,
+    /// Not sent (or failed transiently); still queued
+    Pending,
+}
+
+/// How many queued writes `rr queue flush` left in each [`FlushStatus`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlushCounts {
+    pub applied: usize,
+    pub conflicts: usize,
+    pub failed: usize,
+    pub pending: usize,
+}
+
+impl FlushCounts {
+    pub fn record(&mut self, status: &FlushStatus) {
+        match status {
+            FlushStatus::Applied => self.applied += 1,
+            FlushStatus::Conflict => self.conflicts += 1,
+            FlushStatus::Failed => self.failed += 1,
+            FlushStatus::Pending => self.pending += 1,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlushResult {
+    pub op: String,
+    pub status: FlushStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// What `rr restore` did with the documents in a backup
+#[derive(Debug, Default, Serialize)]
+pub struct RestoreReport {
+    pub created: usize,
+    /// Already in Reader, by URL
+    pub existing: usize,
+    /// Highlights and notes, which can't be recreated on their own
+    pub skipped_children: usize,
+    pub failed: Vec<SaveFailure>,
+}
+
+/// A document Reader refused to save
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveFailure {
+    /// The document's URL, or its ID if a backup has no source URL for it
+    pub url: String,
+    pub error: String,
+}
@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+/// Retry policy for server errors (HTTP 5xx) and transport failures
+///
+/// Delays grow exponentially from `base_delay`, capped at `max_delay`, and are
+/// randomly shortened by up to `jitter` (0.0-1.0) so concurrent clients don't
+/// retry in lockstep. Retrying stops after `max_attempts` total attempts or
+/// once `max_total_wait` has been spent sleeping.
+///
+/// Rate limiting (HTTP 429) is handled separately and is not limited by this policy.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: f64,
+    pub max_total_wait: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            max_total_wait: Duration::from_secs(120),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before the attempt following `attempt` (1-based), or `None` if the
+    /// attempt or total-wait budget is exhausted
+    pub fn delay(&self, attempt: u32, waited: Duration) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        let remaining = self.max_total_wait.checked_sub(waited)?;
+        if remaining.is_zero() {
+            return None;
+        }
+
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0) * fastrand::f64();
+        Some(exponential.mul_f64(1.0 - jitter).min(remaining))
+    }
+
+    /// Whether a response with this status is worth retrying
+    ///
+    /// Only idempotent requests are retried: a POST to `/v3/save/` that failed
+    /// with a 5xx may still have created the document.
+    pub fn is_retryable_status(method: &str, status: StatusCode) -> bool {
+        is_idempotent(method)
+            && matches!(
+                status,
+                StatusCode::INTERNAL_SERVER_ERROR
+                    | StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            )
+    }
+
+    /// Whether a transport error is worth retrying
+    ///
+    /// Connection failures happen before anything is sent, so they are safe to
+    /// retry for every method; timeouts and dropped connections only for
+    /// idempotent ones.
+    pub fn is_retryable_error(method: &str, error: &reqwest::Error) -> bool {
+        error.is_connect()
+            || (is_idempotent(method)
+                && (error.is_timeout() || error.is_request() || error.is_body()))
+    }
+}
+
+/// POST is the only method the client uses that isn't safe to replay. PATCH
+/// bodies set absolute field values, so repeating an update is harmless.
+fn is_idempotent(method: &str) -> bool {
+    method != "POST"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            jitter: 0.0,
+            max_total_wait: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn delay_doubles_up_to_max_delay() {
+        let policy = policy();
+        assert_eq!(
+            policy.delay(1, Duration::ZERO),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.delay(2, Duration::ZERO),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            policy.delay(3, Duration::ZERO),
+            Some(Duration::from_millis(300))
+        );
+    }
+
+    #[test]
+    fn delay_stops_at_max_attempts() {
+        let policy = policy();
+        assert_eq!(policy.delay(4, Duration::ZERO), None);
+        assert_eq!(policy.delay(10, Duration::ZERO), None);
+        assert_eq!(RetryPolicy::none().delay(1, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn delay_is_capped_by_remaining_total_wait() {
+        let policy = policy();
+        assert_eq!(
+            policy.delay(3, Duration::from_millis(950)),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(policy.delay(1, Duration::from_secs(1)), None);
+        assert_eq!(policy.delay(1, Duration::from_secs(5)), None);
+    }
+
+    #[test]
+    fn jitter_only_shortens_the_delay() {
+        let policy = RetryPolicy {
+            jitter: 1.0,
+            ..policy()
+        };
+        for _ in 0..100 {
+            let delay = policy.delay(2, Duration::ZERO).unwrap();
+            assert!(delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn large_attempt_numbers_do_not_overflow() {
+        let policy = RetryPolicy {
+            max_attempts: u32::MAX,
+            ..policy()
+        };
+        assert_eq!(
+            policy.delay(100, Duration::ZERO),
+            Some(Duration::from_millis(300))
+        );
+    }
+
+    #[test]
+    fn only_idempotent_requests_retry_server_errors() {
+        assert!(RetryPolicy::is_retryable_status(
+            "GET",
+            StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(!RetryPolicy::is_retryable_status(
+            "POST",
+            StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(!RetryPolicy::is_retryable_status(
+            "GET",
+            StatusCode::NOT_IMPLEMENTED
+        ));
+    }
+}
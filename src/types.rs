@@ -2,7 +2,8 @@ use serde::{Deserialize, Serialize};
 
 // === Request Types ===
 
-#[derive(Debug, Serialize)]
+/// Body of `POST /v3/save/`; only `url` is required
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct CreateDocumentRequest {
     pub url: String,
 
@@ -43,7 +44,8 @@ pub struct CreateDocumentRequest {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+/// Body of `PATCH /v3/update/<id>/`; unset fields are left unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateDocumentRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -73,22 +75,152 @@ pub struct UpdateDocumentRequest {
     pub tags: Option<Vec<String>>,
 }
 
-#[derive(Debug, Default)]
+/// Query parameters for `GET /v3/list/`; unset fields are not sent
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct ListDocumentsParams {
+    /// Return only the document with this ID
     pub id: Option<String>,
+    /// Only documents updated after this ISO 8601 timestamp
     pub updated_after: Option<String>,
+    /// `new`, `later`, `shortlist`, `archive` or `feed`
     pub location: Option<String>,
+    /// `article`, `email`, `rss`, `highlight`, `note`, `pdf`, `epub`, `tweet` or `video`
     pub category: Option<String>,
+    /// Tag key (see [`Tag::key`])
     pub tag: Option<String>,
+    /// Cursor from a previous page's `next_page_cursor`
     pub page_cursor: Option<String>,
+    /// Include `html_content` in each document
     pub with_html_content: Option<bool>,
+    /// Include the raw source URL in each document
     pub with_raw_source_url: Option<bool>,
 }
 
+impl ListDocumentsParams {
+    /// Whether `doc` passes the `id`, `location`, `category`, `tag` and
+    /// `updated_after` filters, as the API would apply them
+    ///
+    /// Used to query stored documents locally. Timestamps are compared as
+    /// instants, whatever their offset and precision; ones that don't parse
+    /// as ISO 8601 are compared as strings.
+    pub fn matches(&self, doc: &Document) -> bool {
+        fn matches_field(filter: &Option<String>, value: &Option<String>) -> bool {
+            filter.is_none() || filter == value
+        }
+
+        self.id.as_ref().is_none_or(|id| *id == doc.id)
+            && matches_field(&self.location, &doc.location)
+            && matches_field(&self.category, &doc.category)
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|tag| doc.tag_keys().contains(tag))
+            && self.updated_after.as_ref().is_none_or(|after| {
+                doc.updated_at
+                    .as_ref()
+                    .is_some_and(|updated| is_later(updated, after))
+            })
+    }
+}
+
+/// Whether the ISO 8601 timestamp `a` is later than `b`
+fn is_later(a: &str, b: &str) -> bool {
+    match (parse_timestamp(a), parse_timestamp(b)) {
+        (Some(a), Some(b)) => a > b,
+        _ => a > b,
+    }
+}
+
+/// Parse an ISO 8601 date or date-time into nanoseconds since the Unix
+/// epoch
+///
+/// Accepts `YYYY-MM-DD`, optionally followed by `T` (or a space),
+/// `HH:MM[:SS[.fraction]]` and `Z` or an offset (`+HH:MM`, `+HHMM`, `+HH`).
+/// Times without an offset are taken as UTC.
+pub(crate) fn parse_timestamp(value: &str) -> Option<i128> {
+    fn number(digits: &str) -> Option<i64> {
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())
+            .flatten()
+    }
+
+    let value = value.trim();
+    let (date, rest) = value.split_at_checked(10)?;
+    let mut parts = date.split('-');
+    let (year, month, day) = (
+        number(parts.next().filter(|p| p.len() == 4)?)?,
+        number(parts.next().filter(|p| p.len() == 2)?)?,
+        number(parts.next().filter(|p| p.len() == 2)?)?,
+    );
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut seconds = 0;
+    let mut nanos: i128 = 0;
+    let mut offset = 0;
+    if let Some(time) = rest.strip_prefix(['T', 't', ' ']) {
+        let zone_at = time.find(['Z', 'z', '+', '-']).unwrap_or(time.len());
+        let (time, zone) = time.split_at(zone_at);
+        let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+        let mut fields = time.split(':');
+        let hour = number(fields.next().filter(|f| f.len() == 2)?)?;
+        let minute = number(fields.next().filter(|f| f.len() == 2)?)?;
+        let second = match fields.next() {
+            Some(f) if f.len() == 2 => number(f)?,
+            Some(_) => return None,
+            None => 0,
+        };
+        if fields.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+        seconds = hour * 3_600 + minute * 60 + second;
+        if !fraction.is_empty() {
+            number(fraction)?;
+            let digits = &fraction[..fraction.len().min(9)];
+            nanos = digits.parse::<i128>().ok()? * 10i128.pow(9 - digits.len() as u32);
+        }
+
+        offset = match zone {
+            "" | "Z" | "z" => 0,
+            _ => {
+                let sign = if zone.starts_with('-') { -1 } else { 1 };
+                let zone = zone[1..].replace(':', "");
+                if zone.len() != 2 && zone.len() != 4 {
+                    return None;
+                }
+                let hours = number(&zone[..2])?;
+                let minutes = if zone.len() == 4 {
+                    number(&zone[2..])?
+                } else {
+                    0
+                };
+                sign * (hours * 3_600 + minutes * 60)
+            }
+        };
+    } else if !rest.is_empty() {
+        return None;
+    }
+
+    // Days-from-civil conversion (proleptic Gregorian calendar)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86_400 + seconds - offset;
+    Some(i128::from(secs) * 1_000_000_000 + nanos)
+}
+
 // === Response Types ===
 
 /// Document response - all fields optional except id to handle API variations
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Document {
     pub id: String,
     #[serde(default)]
@@ -120,6 +252,50 @@ pub struct Document {
     pub extra: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
+impl Document {
+    /// Keys of the document's tags
+    ///
+    /// Reader returns tags as an object keyed by tag key; plain arrays of
+    /// names are accepted too.
+    pub fn tag_keys(&self) -> Vec<String> {
+        match &self.tags {
+            Some(serde_json::Value::Object(obj)) => obj.keys().cloned().collect(),
+            Some(serde_json::Value::Array(arr)) => arr
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The document's tags, named as Reader names them where the response
+    /// includes names (falling back to the key)
+    pub fn tags(&self) -> Vec<Tag> {
+        match &self.tags {
+            Some(serde_json::Value::Object(obj)) => obj
+                .iter()
+                .map(|(key, value)| Tag {
+                    key: key.clone(),
+                    name: value
+                        .get("name")
+                        .and_then(|n| n.as_str())
+                        .unwrap_or(key)
+                        .to_string(),
+                })
+                .collect(),
+            _ => self
+                .tag_keys()
+                .into_iter()
+                .map(|key| Tag {
+                    name: key.clone(),
+                    key,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One page of `GET /v3/list/` results
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ListDocumentsResponse {
     pub count: u32,
@@ -128,18 +304,21 @@ pub struct ListDocumentsResponse {
     pub results: Vec<Document>,
 }
 
+/// Response of `POST /v3/save/`
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateDocumentResponse {
     pub id: String,
     pub url: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// A tag; `key` is what list filters expect, `name` is what users see
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Tag {
     pub key: String,
     pub name: String,
 }
 
+/// One page of `GET /v3/tags/` results
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ListTagsResponse {
     pub count: u32,
@@ -147,3 +326,135 @@ pub struct ListTagsResponse {
     pub next_page_cursor: Option<String>,
     pub results: Vec<Tag>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(value: serde_json::Value) -> Document {
+        let mut doc = serde_json::json!({"id": "d1", "source_url": null, "title": null});
+        doc.as_object_mut()
+            .unwrap()
+            .extend(value.as_object().unwrap().clone());
+        serde_json::from_value(doc).unwrap()
+    }
+
+    #[test]
+    fn matches_id_location_and_category() {
+        let d = doc(serde_json::json!({"location": "later", "category": "pdf"}));
+        assert!(ListDocumentsParams::default().matches(&d));
+        for (params, expected) in [
+            (
+                ListDocumentsParams {
+                    id: Some("d1".into()),
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                ListDocumentsParams {
+                    id: Some("d2".into()),
+                    ..Default::default()
+                },
+                false,
+            ),
+            (
+                ListDocumentsParams {
+                    location: Some("later".into()),
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                ListDocumentsParams {
+                    location: Some("archive".into()),
+                    ..Default::default()
+                },
+                false,
+            ),
+            (
+                ListDocumentsParams {
+                    category: Some("pdf".into()),
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                ListDocumentsParams {
+                    category: Some("rss".into()),
+                    ..Default::default()
+                },
+                false,
+            ),
+        ] {
+            assert_eq!(params.matches(&d), expected, "{:?}", params);
+        }
+        let no_location = doc(serde_json::json!({}));
+        let later = ListDocumentsParams {
+            location: Some("later".into()),
+            ..Default::default()
+        };
+        assert!(!later.matches(&no_location));
+    }
+
+    #[test]
+    fn matches_tag_keys_in_objects_and_arrays() {
+        let tagged = ListDocumentsParams {
+            tag: Some("rust".into()),
+            ..Default::default()
+        };
+        assert!(tagged.matches(&doc(
+            serde_json::json!({"tags": {"rust": {"name": "Rust"}}})
+        )));
+        assert!(tagged.matches(&doc(serde_json::json!({"tags": ["cli", "rust"]}))));
+        assert!(!tagged.matches(&doc(serde_json::json!({"tags": {"go": {"name": "Go"}}}))));
+        assert!(!tagged.matches(&doc(serde_json::json!({}))));
+    }
+
+    #[test]
+    fn matches_updated_after_across_offsets_and_precision() {
+        let after = |value: &str| ListDocumentsParams {
+            updated_after: Some(value.to_string()),
+            ..Default::default()
+        };
+        let d = doc(serde_json::json!({"updated_at": "2024-05-01T12:00:00.250000+00:00"}));
+
+        assert!(after("2024-05-01T11:59:59Z").matches(&d));
+        assert!(after("2024-05-01T12:00:00Z").matches(&d));
+        assert!(!after("2024-05-01T12:00:00.5Z").matches(&d));
+        // 13:30+02:00 is 11:30 UTC, although it sorts later as a string
+        assert!(after("2024-05-01T13:30:00+02:00").matches(&d));
+        assert!(!after("2024-05-01T08:30:00-04:00").matches(&d));
+        assert!(after("2024-05-01").matches(&d));
+        assert!(!after("2024-05-02").matches(&d));
+        assert!(!after("2024-04-01").matches(&doc(serde_json::json!({}))));
+    }
+
+    #[test]
+    fn parse_timestamp_formats() {
+        let noon = parse_timestamp("2024-05-01T12:00:00Z").unwrap();
+        assert_eq!(noon, 1_714_564_800 * 1_000_000_000);
+        assert_eq!(parse_timestamp("2024-05-01 14:00+02:00"), Some(noon));
+        assert_eq!(parse_timestamp("2024-05-01T07:00:00-0500"), Some(noon));
+        assert_eq!(parse_timestamp("2024-05-01T12:00:00"), Some(noon));
+        assert_eq!(parse_timestamp("1970-01-01"), Some(0));
+        assert_eq!(
+            parse_timestamp("1969-12-31T23:59:59Z"),
+            Some(-1_000_000_000)
+        );
+        assert_eq!(
+            parse_timestamp("2024-05-01T12:00:00.123456789123Z"),
+            Some(noon + 123_456_789)
+        );
+        for bad in [
+            "",
+            "yesterday",
+            "2024-5-1",
+            "2024-13-01",
+            "2024-05-01T25:00",
+            "2024-05-01Tnoon",
+        ] {
+            assert_eq!(parse_timestamp(bad), None, "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Small helpers shared by several commands
+
+use sha2::{Digest, Sha256};
+
+/// Format a Unix timestamp as `YYYY-MM-DD HH:MM:SS UTC`
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+
+    // Civil-from-days conversion (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+/// Decode the HTML character references found in bookmark exports and
+/// local HTML files
+pub fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => match entity.strip_prefix('#') {
+                    Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16)
+                        .ok()
+                        .and_then(char::from_u32),
+                    Some(dec) => dec.parse().ok().and_then(char::from_u32),
+                    None => None,
+                },
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Hex SHA-256 of `input`, used to tell files apart
+pub fn input_sha256(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Plain text as HTML: blank lines separate paragraphs and other line breaks
+/// are kept
+pub fn text_to_html(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|para| !para.is_empty())
+        .map(|para| {
+            let escaped = para
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('\n', "<br>\n");
+            format!("<p>{}</p>\n", escaped)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_entities_handles_named_and_numeric_references() {
+        assert_eq!(
+            decode_entities("a &amp; b &lt;c&gt; &quot;d&quot; &#39;e&apos;"),
+            "a & b <c> \"d\" 'e'"
+        );
+        assert_eq!(decode_entities("&#233;t&#xE9; &#X1F600;"), "été 😀");
+        assert_eq!(decode_entities("caf&nbsp;"), "caf ");
+    }
+
+    #[test]
+    fn decode_entities_leaves_unknown_references_alone() {
+        assert_eq!(decode_entities("AT&T"), "AT&T");
+        assert_eq!(decode_entities("&bogus; &"), "&bogus; &");
+        assert_eq!(decode_entities("&#xZZ; &#99999999;"), "&#xZZ; &#99999999;");
+        assert_eq!(
+            decode_entities("&averyveryverylongname;"),
+            "&averyveryverylongname;"
+        );
+    }
+
+    #[test]
+    fn text_to_html_splits_paragraphs_and_escapes() {
+        assert_eq!(
+            text_to_html("a < b\r\nc\n\n\n\nd & e\n"),
+            "<p>a &lt; b<br>\nc</p>\n<p>d &amp; e</p>\n"
+        );
+        assert_eq!(text_to_html("\n\n"), "");
+    }
+}